@@ -3,6 +3,7 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use quote::quote;
+use syn::parse::Parser;
 //use syn::{AttributeArgs, NestedMeta};
 //use syn::{AttributeArgs, ItemFn, NestedMeta};
 
@@ -32,6 +33,9 @@ pub fn actor_component_derive(input: TokenStream) -> TokenStream {
     gen.into()
 }
 
+/// Registers an actor with a hand-written `Inject` impl and generates its `Handler<FactoryEvent>`.
+/// Only actors with `Context<Self>` are supported; for `SyncContext` actors use
+/// `#[bean(inject, register, sync = "N")]` instead.
 #[proc_macro_derive(InjectComponent)]
 pub fn inject_component_derive(input: TokenStream) -> TokenStream {
     let ast: syn::DeriveInput = syn::parse(input).unwrap();
@@ -50,6 +54,14 @@ pub fn inject_component_derive(input: TokenStream) -> TokenStream {
                     ::bean_factory::FactoryEvent::Complete => {
                         ::bean_factory::Inject::complete(self, ctx);
                     }
+                    ::bean_factory::FactoryEvent::Destroy => {
+                        ::bean_factory::Inject::destroy(self, ctx);
+                        ::bean_factory::ActorContext::stop(ctx);
+                    }
+                    ::bean_factory::FactoryEvent::Refreshed { bean } => {
+                        ::bean_factory::Inject::refreshed(self, bean, ctx);
+                    }
+                    _ => {}
                 }
             }
         }
@@ -61,17 +73,56 @@ pub fn inject_component_derive(input: TokenStream) -> TokenStream {
     gen.into()
 }
 
-/// Full feature example: `#[bean(actor, inject)]`
+/// Full feature example:
+/// `#[bean(actor, inject, register, lazy, primary, name = "bean_name", complete = "on_complete", destroy = "on_destroy")]`
+///
+/// With `inject`, fields marked `#[inject]` or `#[inject(name = "...", required)]` are injected
+/// by a generated `Inject` impl. Supported field types are `Option<Addr<T>>`, `Option<Arc<T>>`
+/// (including `Option<Arc<dyn Trait>>`), and the collections `Vec<Addr<T>>` and `Vec<Arc<T>>`
+/// (including `Vec<Arc<dyn Trait>>`).
+/// Injected fields are returned by `Inject::dependencies` as inject-only dependencies, which the
+/// `actor_with_inject_*` constructors declare; they order init and shutdown unless that would
+/// close a cycle, so actors may inject each other.
+/// A `required` field fails `init` when its bean is missing. Any single-bean field looked up by
+/// type fails `init` when several beans match and none is primary.
+/// `complete` names a `fn(&mut self, &mut Self::Context)` method called after injection.
+/// `destroy` names a method with the same signature called on shutdown, before the actor stops.
+/// `refreshed` names a `fn(&mut self, String, &mut Self::Context)` method called after a
+/// dependency is replaced at runtime and the actor is re-injected.
+/// `provides = "dyn Trait"` (repeatable) exposes a registered non-actor bean as `Arc<dyn Trait>`.
+/// `handles = "Message"` (repeatable) exposes a registered actor as `Recipient<Message>`.
+/// Fields of type `Option<Recipient<M>>` or `Vec<Recipient<M>>` are injected from such actors.
+/// `primary` makes the bean win unqualified lookups when several beans match.
+/// `supervised` (requires `inject`) runs the actor under `actix::Supervisor`; after a restart the
+/// actor and the actors depending on it are re-injected. It adds a hidden field keeping the
+/// `BeanFactory`, so build the struct with `Default`. The actor only stops on shutdown once every
+/// address outside the factory is dropped.
+/// `sync` (requires `inject`) supports actors with `SyncContext`; with `register`, `sync = "4"`
+/// runs the actor on 4 `SyncArbiter` threads, each of which receives the inject events. On
+/// shutdown the actor is not stopped; its threads exit once every address is dropped.
+/// `profile = "dev"` (repeatable), `on_property = "key"` (repeatable) and `on_missing_bean`
+/// register the bean only when the condition holds at `init`.
+/// `#[inject(property = "db.url")]` on an `Option<T>` field injects a typed property.
+/// `config = "server"` registers a `serde::Deserialize` struct bound from the `server.*` properties;
+/// `validate = "method"` checks it with `fn method(&self) -> Result<(), E>` during `init`.
 #[proc_macro_attribute]
 pub fn bean(args: TokenStream, input: TokenStream) -> TokenStream {
     let arg_str = args.to_string();
     let config = read_bean_config(&arg_str);
-    let input_clone: proc_macro2::TokenStream = input.clone().into();
-    let ast = syn::parse(input).unwrap();
-    let stream = impl_bean_derive(&ast, config);
+    let mut ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let inject_fields = match read_inject_fields(&mut ast) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    if config.is_supervised {
+        if let Err(e) = add_factory_field(&mut ast) {
+            return e.to_compile_error().into();
+        }
+    }
+    let stream = impl_bean_derive(&ast, config, &inject_fields);
     let s: proc_macro2::TokenStream = stream.into();
     let qt = quote! {
-       #input_clone
+       #ast
        #s
     };
     qt.into()
@@ -82,16 +133,56 @@ pub(crate) struct BeanConfig {
     pub is_actor: bool,
     pub is_inject: bool,
     pub is_register: bool,
+    pub is_lazy: bool,
+    pub is_primary: bool,
+    pub is_supervised: bool,
+    pub is_sync: bool,
+    pub sync_threads: Option<String>,
+    pub name: Option<String>,
+    pub complete: Option<String>,
+    pub destroy: Option<String>,
+    pub refreshed: Option<String>,
+    pub provides: Vec<String>,
+    pub handles: Vec<String>,
+    pub profiles: Vec<String>,
+    pub on_missing_bean: bool,
+    pub on_property: Vec<String>,
+    pub config: Option<String>,
+    pub validate: Option<String>,
 }
 
 ///
 /// read bean config
-/// actor,inject,register
+/// actor,inject,register,lazy,primary,supervised,sync,sync = "threads",name = "...",complete = "...",destroy = "...",refreshed = "...",
+/// provides = "dyn Trait",
+/// handles = "Message",profile = "dev",on_missing_bean,on_property = "key",config = "prefix",
+/// validate = "method"
 fn read_bean_config(arg: &str) -> BeanConfig {
     let mut config = BeanConfig::default();
-    let keys: Vec<&str> = arg.split(",").collect();
+    let keys: Vec<&str> = arg.split(',').collect();
     for key in keys {
         let item = key.trim();
+        if let Some((key, value)) = item.split_once('=') {
+            let value = value.trim().trim_matches('"').to_owned();
+            match key.trim() {
+                "name" => config.name = Some(value),
+                "complete" => config.complete = Some(value),
+                "destroy" => config.destroy = Some(value),
+                "refreshed" => config.refreshed = Some(value),
+                "provides" => config.provides.push(value),
+                "handles" => config.handles.push(value),
+                "profile" => config.profiles.push(value),
+                "on_property" => config.on_property.push(value),
+                "config" => config.config = Some(value),
+                "validate" => config.validate = Some(value),
+                "sync" => {
+                    config.is_sync = true;
+                    config.sync_threads = Some(value);
+                }
+                _ => {}
+            }
+            continue;
+        }
         match item {
             "actor" => config.is_actor = true,
             "inject" => {
@@ -99,14 +190,464 @@ fn read_bean_config(arg: &str) -> BeanConfig {
                 config.is_inject = true;
             }
             "register" => config.is_register = true,
+            "lazy" => config.is_lazy = true,
+            "primary" => config.is_primary = true,
+            "on_missing_bean" => config.on_missing_bean = true,
+            "supervised" => config.is_supervised = true,
+            "sync" => config.is_sync = true,
             _ => {}
         }
     }
     config
 }
 
-fn impl_bean_derive(ast: &syn::DeriveInput, config: BeanConfig) -> TokenStream {
+pub(crate) enum InjectKind {
+    /// `Option<Addr<T>>`
+    Actor,
+    /// `Option<Arc<T>>`
+    Bean,
+    /// `Option<Arc<dyn Trait>>`
+    DynBean,
+    /// `Vec<Addr<T>>`
+    Actors,
+    /// `Vec<Arc<T>>`
+    Beans,
+    /// `Vec<Arc<dyn Trait>>`
+    DynBeans,
+    /// `Option<Recipient<M>>`
+    Recipient,
+    /// `Vec<Recipient<M>>`
+    Recipients,
+    /// `Option<T>` with `property = "..."`
+    Property,
+}
+
+/// field marked by `#[inject]`
+pub(crate) struct InjectField {
+    pub ident: syn::Ident,
+    pub kind: InjectKind,
+    pub bean_type: syn::Type,
+    pub name: Option<String>,
+    pub property: Option<String>,
+    pub required: bool,
+}
+
+/// field type -> inject kind and bean type
+fn read_inject_kind(ty: &syn::Type) -> Option<(InjectKind, syn::Type)> {
+    if let Some(inner) = generic_arg(ty, "Option") {
+        if let Some(ty) = generic_arg(inner, "Addr") {
+            return Some((InjectKind::Actor, ty.clone()));
+        }
+        if let Some(ty) = generic_arg(inner, "Recipient") {
+            return Some((InjectKind::Recipient, ty.clone()));
+        }
+        return match generic_arg(inner, "Arc")? {
+            ty @ syn::Type::TraitObject(_) => Some((InjectKind::DynBean, ty.clone())),
+            ty => Some((InjectKind::Bean, ty.clone())),
+        };
+    }
+    let inner = generic_arg(ty, "Vec")?;
+    if let Some(ty) = generic_arg(inner, "Addr") {
+        return Some((InjectKind::Actors, ty.clone()));
+    }
+    if let Some(ty) = generic_arg(inner, "Recipient") {
+        return Some((InjectKind::Recipients, ty.clone()));
+    }
+    match generic_arg(inner, "Arc")? {
+        ty @ syn::Type::TraitObject(_) => Some((InjectKind::DynBeans, ty.clone())),
+        ty => Some((InjectKind::Beans, ty.clone())),
+    }
+}
+
+/// `Wrapper<T>` -> `T`
+fn generic_arg<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
+    let path = match ty {
+        syn::Type::Path(v) if v.qself.is_none() => &v.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first() {
+                Some(syn::GenericArgument::Type(ty)) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn read_inject_attr(attr: &syn::Attribute, field: &mut InjectField) -> syn::Result<()> {
+    match attr.parse_meta()? {
+        syn::Meta::Path(_) => Ok(()),
+        syn::Meta::List(list) => {
+            for item in list.nested {
+                match item {
+                    syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("required") => {
+                        field.required = true;
+                    }
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(v)) if v.path.is_ident("name") => {
+                        match v.lit {
+                            syn::Lit::Str(s) => field.name = Some(s.value()),
+                            lit => return Err(syn::Error::new_spanned(lit, "expected string")),
+                        }
+                    }
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(v))
+                        if v.path.is_ident("property") =>
+                    {
+                        match v.lit {
+                            syn::Lit::Str(s) => field.property = Some(s.value()),
+                            lit => return Err(syn::Error::new_spanned(lit, "expected string")),
+                        }
+                    }
+                    item => {
+                        return Err(syn::Error::new_spanned(
+                            item,
+                            "unsupported inject option, expected `name = \"...\"`, `property = \"...\"` or `required`",
+                        ))
+                    }
+                }
+            }
+            Ok(())
+        }
+        meta => Err(syn::Error::new_spanned(meta, "expected #[inject] or #[inject(...)]")),
+    }
+}
+
+/// `supervised`的actor增加保存工厂的字段,重启时通过它通知工厂
+fn add_factory_field(ast: &mut syn::DeriveInput) -> syn::Result<()> {
+    match &mut ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => {
+            let field = syn::Field::parse_named.parse2(quote! {
+                #[doc(hidden)]
+                __bean_factory: ::std::option::Option<::bean_factory::BeanFactory>
+            })?;
+            fields.named.push(field);
+            Ok(())
+        }
+        _ => Err(syn::Error::new_spanned(
+            &ast.ident,
+            "`supervised` requires a struct with named fields",
+        )),
+    }
+}
+
+/// read and remove `#[inject]` attributes from struct fields
+fn read_inject_fields(ast: &mut syn::DeriveInput) -> syn::Result<Vec<InjectField>> {
+    let mut inject_fields = vec![];
+    let fields = match &mut ast.data {
+        syn::Data::Struct(data) => &mut data.fields,
+        _ => return Ok(inject_fields),
+    };
+    for field in fields.iter_mut() {
+        let (inject_attrs, attrs): (Vec<_>, Vec<_>) = field
+            .attrs
+            .drain(..)
+            .partition(|attr| attr.path.is_ident("inject"));
+        field.attrs = attrs;
+        if inject_attrs.is_empty() {
+            continue;
+        }
+        let ident = match &field.ident {
+            Some(ident) => ident.clone(),
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "#[inject] only supports named fields",
+                ))
+            }
+        };
+        let (kind, bean_type) = match read_inject_kind(&field.ty) {
+            Some(v) => v,
+            None => match generic_arg(&field.ty, "Option") {
+                Some(ty) => (InjectKind::Property, ty.clone()),
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        &field.ty,
+                        "#[inject] field must be Option<Addr<T>>, Option<Arc<T>>, Option<Recipient<M>> or a Vec of them",
+                    ))
+                }
+            },
+        };
+        let mut inject_field = InjectField {
+            ident,
+            kind,
+            bean_type,
+            name: None,
+            property: None,
+            required: false,
+        };
+        for attr in &inject_attrs {
+            read_inject_attr(attr, &mut inject_field)?;
+        }
+        if inject_field.property.is_some() {
+            if inject_field.name.is_some() || inject_field.required {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "`property` can not be used with `name` or `required`",
+                ));
+            }
+            match generic_arg(&field.ty, "Option") {
+                Some(ty) => {
+                    inject_field.kind = InjectKind::Property;
+                    inject_field.bean_type = ty.clone();
+                }
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        &field.ty,
+                        "`property` field must be Option<T>",
+                    ))
+                }
+            }
+        } else if matches!(inject_field.kind, InjectKind::Property) {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "#[inject] field must be Option<Addr<T>>, Option<Arc<T>>, Option<Recipient<M>> or a Vec of them",
+            ));
+        }
+        let named_kind = matches!(
+            inject_field.kind,
+            InjectKind::Actor | InjectKind::Bean | InjectKind::Recipient
+        );
+        if inject_field.name.is_some() && !named_kind {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "`name` only supports Option<Addr<T>>, Option<Arc<T>> and Option<Recipient<M>> fields",
+            ));
+        }
+        inject_fields.push(inject_field);
+    }
+    Ok(inject_fields)
+}
+
+fn impl_inject(
+    ast: &syn::DeriveInput,
+    config: &BeanConfig,
+    inject_fields: &[InjectField],
+) -> proc_macro2::TokenStream {
     let name = &ast.ident;
+    let assigns = inject_fields.iter().map(|field| {
+        let ident = &field.ident;
+        match (&field.kind, &field.name) {
+            (InjectKind::Actor, None) => quote! { self.#ident = factory_data.get_actor(); },
+            (InjectKind::Actor, Some(bean_name)) => {
+                quote! { self.#ident = factory_data.get_actor_by_name(#bean_name); }
+            }
+            (InjectKind::Bean, None) => quote! { self.#ident = factory_data.get_bean(); },
+            (InjectKind::Bean, Some(bean_name)) => {
+                quote! { self.#ident = factory_data.get_bean_by_name(#bean_name); }
+            }
+            (InjectKind::DynBean, _) => quote! { self.#ident = factory_data.get_bean_dyn(); },
+            (InjectKind::Actors, _) => quote! { self.#ident = factory_data.get_actors(); },
+            (InjectKind::Beans, _) => quote! { self.#ident = factory_data.get_beans(); },
+            (InjectKind::DynBeans, _) => quote! { self.#ident = factory_data.get_beans_of(); },
+            (InjectKind::Recipient, None) => quote! { self.#ident = factory_data.get_recipient(); },
+            (InjectKind::Recipient, Some(bean_name)) => {
+                quote! { self.#ident = factory_data.get_recipient_by_name(#bean_name); }
+            }
+            (InjectKind::Recipients, _) => {
+                quote! { self.#ident = factory_data.get_recipients(); }
+            }
+            (InjectKind::Property, _) => {
+                let key = field.property.as_deref().unwrap_or_default();
+                quote! { self.#ident = factory_data.get_property_opt(#key); }
+            }
+        }
+    });
+    let depends = inject_fields
+        .iter()
+        .filter(|field| !matches!(field.kind, InjectKind::Property))
+        .map(|field| {
+            let bean_type = match &field.kind {
+                InjectKind::Recipient | InjectKind::Recipients => {
+                    let message_type = &field.bean_type;
+                    quote! { ::bean_factory::Recipient<#message_type> }
+                }
+                _ => {
+                    let bean_type = &field.bean_type;
+                    quote! { #bean_type }
+                }
+            };
+            let required = field.required;
+            let multiple = matches!(
+                field.kind,
+                InjectKind::Actors | InjectKind::Beans | InjectKind::DynBeans | InjectKind::Recipients
+            );
+            match &field.name {
+                Some(bean_name) => {
+                    quote! { ::bean_factory::BeanDependency::inject_by_name(#bean_name, #required) }
+                }
+                None if multiple => {
+                    quote! { ::bean_factory::BeanDependency::inject_all_of::<#bean_type>(#required) }
+                }
+                None => quote! { ::bean_factory::BeanDependency::inject_of::<#bean_type>(#required) },
+            }
+        });
+    let complete = match &config.complete {
+        Some(method) => {
+            let method = syn::Ident::new(method, proc_macro2::Span::call_site());
+            quote! {
+                fn complete(&mut self, ctx: &mut Self::Context) {
+                    self.#method(ctx);
+                }
+            }
+        }
+        None => quote! {},
+    };
+    let destroy = match &config.destroy {
+        Some(method) => {
+            let method = syn::Ident::new(method, proc_macro2::Span::call_site());
+            quote! {
+                fn destroy(&mut self, ctx: &mut Self::Context) {
+                    self.#method(ctx);
+                }
+            }
+        }
+        None => quote! {},
+    };
+    let refreshed = match &config.refreshed {
+        Some(method) => {
+            let method = syn::Ident::new(method, proc_macro2::Span::call_site());
+            quote! {
+                fn refreshed(&mut self, bean: String, ctx: &mut Self::Context) {
+                    self.#method(bean, ctx);
+                }
+            }
+        }
+        None => quote! {},
+    };
+    quote! {
+        impl ::bean_factory::Inject for #name {
+            type Context = <Self as ::bean_factory::Actor>::Context;
+
+            fn inject(
+                &mut self,
+                factory_data: ::bean_factory::FactoryData,
+                _factory: ::bean_factory::BeanFactory,
+                _ctx: &mut Self::Context,
+            ) {
+                #(#assigns)*
+            }
+
+            fn dependencies() -> ::std::vec::Vec<::bean_factory::BeanDependency> {
+                ::std::vec![#(#depends),*]
+            }
+
+            #complete
+
+            #destroy
+
+            #refreshed
+        }
+    }
+}
+
+fn impl_bean_derive(
+    ast: &syn::DeriveInput,
+    config: BeanConfig,
+    inject_fields: &[InjectField],
+) -> TokenStream {
+    let name = &ast.ident;
+    if !inject_fields.is_empty() && !config.is_inject {
+        return syn::Error::new_spanned(name, "#[inject] fields require #[bean(inject)]")
+            .to_compile_error()
+            .into();
+    }
+    if config.complete.is_some() && inject_fields.is_empty() {
+        return syn::Error::new_spanned(name, "`complete` requires #[inject] fields")
+            .to_compile_error()
+            .into();
+    }
+    if config.destroy.is_some() && inject_fields.is_empty() {
+        return syn::Error::new_spanned(name, "`destroy` requires #[inject] fields")
+            .to_compile_error()
+            .into();
+    }
+    if config.refreshed.is_some() && inject_fields.is_empty() {
+        return syn::Error::new_spanned(name, "`refreshed` requires #[inject] fields")
+            .to_compile_error()
+            .into();
+    }
+    if config.is_supervised && !config.is_inject {
+        return syn::Error::new_spanned(name, "`supervised` requires `inject`")
+            .to_compile_error()
+            .into();
+    }
+    if config.is_sync && !config.is_inject {
+        return syn::Error::new_spanned(name, "`sync` requires `inject`")
+            .to_compile_error()
+            .into();
+    }
+    if config.is_sync && config.is_supervised {
+        return syn::Error::new_spanned(name, "`sync` can not be used with `supervised`")
+            .to_compile_error()
+            .into();
+    }
+    let sync_threads = match (&config.sync_threads, config.is_sync && config.is_register) {
+        (Some(threads), _) => match threads.parse::<usize>() {
+            Ok(threads) => Some(threads),
+            Err(_) => {
+                return syn::Error::new_spanned(name, "`sync` expects the number of threads")
+                    .to_compile_error()
+                    .into()
+            }
+        },
+        (None, true) => {
+            return syn::Error::new_spanned(name, "`register` with `sync` requires `sync = \"threads\"`")
+                .to_compile_error()
+                .into()
+        }
+        (None, false) => None,
+    };
+    let sync_impl = if config.is_sync {
+        quote! {
+            impl ::bean_factory::Handler<::bean_factory::SyncFactoryEvent> for #name {
+                type Result = ();
+                fn handle(&mut self, msg: ::bean_factory::SyncFactoryEvent, ctx: &mut Self::Context) -> Self::Result {
+                    msg.handle_with(|event| match event {
+                        //停止后SyncArbiter会重新创建actor,线程在所有地址释放后退出
+                        ::bean_factory::FactoryEvent::Destroy => {
+                            ::bean_factory::Inject::destroy(self, ctx);
+                        }
+                        event => {
+                            ::bean_factory::Handler::<::bean_factory::FactoryEvent>::handle(self, event, ctx)
+                        }
+                    });
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let supervised_impl = if config.is_supervised {
+        quote! {
+            impl ::bean_factory::Supervised for #name {
+                fn restarting(&mut self, ctx: &mut Self::Context) {
+                    if let ::std::option::Option::Some(factory) = self.__bean_factory.as_ref() {
+                        factory.notify_restarted(::bean_factory::AsyncContext::address(ctx));
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let inject_impl = if inject_fields.is_empty() {
+        quote! {}
+    } else {
+        impl_inject(ast, &config, inject_fields)
+    };
+    let keep_factory = if config.is_supervised {
+        quote! { self.__bean_factory = ::std::option::Option::Some(factory.clone()); }
+    } else {
+        quote! {}
+    };
     let inject_handler = if config.is_inject {
         quote! {
             impl ::bean_factory::Handler<::bean_factory::FactoryEvent> for #name {
@@ -117,11 +658,20 @@ fn impl_bean_derive(ast: &syn::DeriveInput, config: BeanConfig) -> TokenStream {
                             factory,
                             factory_data,
                         } => {
+                            #keep_factory
                             ::bean_factory::Inject::inject(self, factory_data, factory, ctx);
                         }
                         ::bean_factory::FactoryEvent::Complete => {
                             ::bean_factory::Inject::complete(self, ctx);
                         }
+                        ::bean_factory::FactoryEvent::Destroy => {
+                            ::bean_factory::Inject::destroy(self, ctx);
+                            ::bean_factory::ActorContext::stop(ctx);
+                        }
+                        ::bean_factory::FactoryEvent::Refreshed { bean } => {
+                            ::bean_factory::Inject::refreshed(self, bean, ctx);
+                        }
+                        _ => {}
                     }
                 }
             }
@@ -129,16 +679,122 @@ fn impl_bean_derive(ast: &syn::DeriveInput, config: BeanConfig) -> TokenStream {
     } else {
         quote! {}
     };
-    let register = if config.is_register {
+    let bean_name = match &config.name {
+        Some(bean_name) => quote! { .with_name(#bean_name) },
+        None => quote! {},
+    };
+    let mut provides = vec![];
+    for provided in &config.provides {
+        let ty: syn::Type = match syn::parse_str(provided) {
+            Ok(ty) => ty,
+            Err(e) => return syn::Error::new_spanned(name, e).to_compile_error().into(),
+        };
+        provides.push(quote! {
+            .provides(|v: ::std::sync::Arc<#name>| v as ::std::sync::Arc<#ty>)
+        });
+    }
+    let mut handles = vec![];
+    for handled in &config.handles {
+        let ty: syn::Type = match syn::parse_str(handled) {
+            Ok(ty) => ty,
+            Err(e) => return syn::Error::new_spanned(name, e).to_compile_error().into(),
+        };
+        handles.push(quote! { .handles::<#name, #ty>() });
+    }
+    if !handles.is_empty() && !config.is_actor {
+        return syn::Error::new_spanned(name, "`handles` requires `actor`")
+            .to_compile_error()
+            .into();
+    }
+    if !provides.is_empty() && config.is_actor {
+        return syn::Error::new_spanned(name, "`provides` is not supported for actors")
+            .to_compile_error()
+            .into();
+    }
+    if config.config.is_some() && config.is_actor {
+        return syn::Error::new_spanned(name, "`config` is not supported for actors")
+            .to_compile_error()
+            .into();
+    }
+    if config.validate.is_some() && config.config.is_none() {
+        return syn::Error::new_spanned(name, "`validate` requires `config`")
+            .to_compile_error()
+            .into();
+    }
+    let primary = if config.is_primary {
+        quote! { .primary() }
+    } else {
+        quote! {}
+    };
+    let mut conditions: Vec<_> = config
+        .profiles
+        .iter()
+        .map(|profile| quote! { .profile(#profile) })
+        .collect();
+    conditions.extend(
+        config
+            .on_property
+            .iter()
+            .map(|key| quote! { .on_property(#key) }),
+    );
+    if config.on_missing_bean {
+        conditions.push(quote! { .on_missing_bean() });
+    }
+    let lazy = if config.is_lazy {
+        quote! { .lazy() }
+    } else {
+        quote! {}
+    };
+    let create = match (&config.config, &config.validate) {
+        (Some(prefix), Some(method)) => {
+            let method = syn::Ident::new(method, proc_macro2::Span::call_site());
+            quote! { ::bean_factory::BeanDefinition::from_config_with(#prefix, #name::#method) }
+        }
+        (Some(prefix), None) => {
+            quote! { ::bean_factory::BeanDefinition::from_config::<#name>(#prefix) }
+        }
+        _ => quote! { ::bean_factory::BeanDefinition::from_default::<#name>() },
+    };
+    let register = if config.is_register || config.config.is_some() {
         match (config.is_actor, config.is_inject) {
+            (true, true) if config.is_sync => quote! {
+                ::bean_factory::submit! {
+                    ::bean_factory::BeanDefinition::sync_actor_from_default::<#name>(#sync_threads)
+                        #bean_name
+                        #lazy
+                        #primary
+                        #(#conditions)*
+                        #(#handles)*
+                }
+            },
+            (true, true) if config.is_supervised => quote! {
+                ::bean_factory::submit! {
+                    ::bean_factory::BeanDefinition::supervised_actor_from_default::<#name>()
+                        #bean_name
+                        #lazy
+                        #primary
+                        #(#conditions)*
+                        #(#handles)*
+                }
+            },
             (true, true) => quote! {
                 ::bean_factory::submit! {
                     ::bean_factory::BeanDefinition::actor_with_inject_from_default::<#name>()
+                        #bean_name
+                        #lazy
+                        #primary
+                        #(#conditions)*
+                        #(#handles)*
                 }
             },
             (true, false) => quote! {
                 ::bean_factory::submit! {
                     ::bean_factory::BeanDefinition::actor_from_default::<#name>()
+                        #bean_name
+                        #lazy
+                        #primary
+                        #(#conditions)*
+                        #(#handles)*
                 }
             },
             (false, true) => quote! {
@@ -146,7 +802,12 @@ fn impl_bean_derive(ast: &syn::DeriveInput, config: BeanConfig) -> TokenStream {
             },
             (false, false) => quote! {
                 ::bean_factory::submit! {
-                    ::bean_factory::BeanDefinition::from_default::<#name>()
+                    #create
+                        #bean_name
+                        #lazy
+                        #primary
+                        #(#conditions)*
+                        #(#provides)*
                 }
             },
         }
@@ -155,6 +816,9 @@ fn impl_bean_derive(ast: &syn::DeriveInput, config: BeanConfig) -> TokenStream {
     };
     let gen = quote! {
         #inject_handler
+        #inject_impl
+        #supervised_impl
+        #sync_impl
         #register
     };
     gen.into()
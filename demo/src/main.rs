@@ -4,7 +4,6 @@ use actix::prelude::*;
 use bean_factory::bean;
 use bean_factory::BeanDefinition;
 use bean_factory::BeanFactory;
-use bean_factory::Inject;
 
 #[bean(actor)]
 #[derive(Default)]
@@ -48,22 +47,15 @@ impl Handler<ConfigCmd> for ConfigService {
     }
 }
 
-#[bean(inject)]
+#[bean(inject, complete = "on_inject_complete")]
 #[derive(Default)]
 pub struct ConfigApi {
-    pub(crate) config_service: Option<Addr<ConfigService>>,
+    #[inject]
+    pub(crate) config_service: Option<Recipient<ConfigCmd>>,
 }
 
-impl Inject for ConfigApi {
-    type Context = Context<Self>;
-
-    fn inject(
-        &mut self,
-        factory_data: bean_factory::FactoryData,
-        _factory: bean_factory::BeanFactory,
-        _ctx: &mut Self::Context,
-    ) {
-        self.config_service = factory_data.get_actor();
+impl ConfigApi {
+    fn on_inject_complete(&mut self, _ctx: &mut Context<Self>) {
         if self.config_service.is_some() {
             println!("ConfigApi:inject success");
         } else {
@@ -83,9 +75,9 @@ impl Handler<ConfigCmd> for ConfigApi {
     fn handle(&mut self, msg: ConfigCmd, _ctx: &mut Self::Context) -> Self::Result {
         let config_service = self.config_service.clone();
         let fut = async {
-            if let Some(addr) = config_service {
+            if let Some(recipient) = config_service {
                 println!("inject success. use inject config_service handle msg");
-                addr.send(msg).await?
+                recipient.send(msg).await?
             } else {
                 Err(anyhow::anyhow!("inject failed. config_service is none"))
             }
@@ -100,9 +92,14 @@ async fn main() -> anyhow::Result<()> {
     std::env::set_var("RUST_LOG","info");
     env_logger::builder().init();
     let factory = BeanFactory::new();
-    factory.register(BeanDefinition::actor_with_inject_from_default::<ConfigApi>());
-    factory.register(BeanDefinition::actor_from_default::<ConfigService>());
-    let _factory_data = factory.init().await;
+    factory.register(
+        BeanDefinition::actor_with_inject_from_default::<ConfigApi>()
+            .require::<Recipient<ConfigCmd>>(),
+    );
+    factory.register(
+        BeanDefinition::actor_from_default::<ConfigService>().handles::<ConfigService, ConfigCmd>(),
+    );
+    let _factory_data = factory.init().await?;
     let api_addr: Addr<ConfigApi> = factory.get_actor().await.unwrap();
     let key = Arc::new("key".to_owned());
     api_addr.do_send(ConfigCmd::Set(
@@ -113,5 +110,6 @@ async fn main() -> anyhow::Result<()> {
         ConfigResult::None => println!("not found value"),
         ConfigResult::Value(val) => println!("query value:{}", &val),
     };
+    factory.shutdown().await?;
     Ok(())
 }
@@ -0,0 +1,115 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeanFactoryError {
+    /// bean创建函数返回错误
+    ProviderFailed { bean: String, message: String },
+    /// bean依赖的bean不存在或创建失败
+    MissingDependency { bean: String, dependency: String },
+    /// bean之间存在循环依赖,值为依赖链,首尾为同一个bean
+    CircularDependency(Vec<String>),
+    /// 同名bean重复注册
+    DuplicateRegistration(String),
+    /// BeanFactoryCore已停止,无法处理消息
+    MailboxClosed,
+    /// 初始化时在超时时间内没有处理完注入事件的actor
+    InjectTimeout(Vec<String>),
+    /// 关闭工厂时在超时时间内没有停止的actor,包括还有地址未释放的受监督actor
+    DestroyTimeout(Vec<String>),
+    /// 按类型没有找到bean
+    BeanNotFound(String),
+    /// 按类型找到多个bean,并且没有唯一的主bean(primary)
+    AmbiguousBean {
+        type_name: String,
+        candidates: Vec<String>,
+    },
+    /// 属性不存在
+    PropertyNotFound(String),
+    /// 属性值无法解析为需要的类型
+    InvalidProperty { key: String, message: String },
+    /// 属性来源(配置文件等)加载失败
+    PropertySource(String),
+    /// 工厂正在初始化,不能同时初始化或替换bean
+    Initializing,
+    /// 初始化期间工厂被关闭
+    InitCancelled,
+    /// 多个bean初始化失败
+    Multiple(Vec<BeanFactoryError>),
+}
+
+impl BeanFactoryError {
+    /// 合并多个错误,只有一个错误时直接返回该错误
+    pub fn from_errors(mut errors: Vec<BeanFactoryError>) -> Self {
+        if errors.len() == 1 {
+            errors.remove(0)
+        } else {
+            BeanFactoryError::Multiple(errors)
+        }
+    }
+
+    /// 展开为错误列表
+    pub fn errors(&self) -> Vec<&BeanFactoryError> {
+        match self {
+            BeanFactoryError::Multiple(errors) => errors.iter().collect(),
+            _ => vec![self],
+        }
+    }
+}
+
+impl fmt::Display for BeanFactoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BeanFactoryError::ProviderFailed { bean, message } => {
+                write!(f, "bean {} create failed: {}", bean, message)
+            }
+            BeanFactoryError::MissingDependency { bean, dependency } => {
+                write!(f, "bean {} missing dependency: {}", bean, dependency)
+            }
+            BeanFactoryError::CircularDependency(chain) => {
+                write!(f, "circular dependency: {}", chain.join(" -> "))
+            }
+            BeanFactoryError::DuplicateRegistration(name) => {
+                write!(f, "bean {} is already registered", name)
+            }
+            BeanFactoryError::MailboxClosed => write!(f, "bean factory mailbox closed"),
+            BeanFactoryError::InjectTimeout(names) => {
+                write!(f, "actors did not respond to inject: {}", names.join(", "))
+            }
+            BeanFactoryError::DestroyTimeout(names) => {
+                write!(f, "actors did not stop: {}", names.join(", "))
+            }
+            BeanFactoryError::BeanNotFound(type_name) => {
+                write!(f, "no bean of type {} found", type_name)
+            }
+            BeanFactoryError::AmbiguousBean {
+                type_name,
+                candidates,
+            } => write!(
+                f,
+                "multiple beans of type {} found, mark one as primary or look up by name: {}",
+                type_name,
+                candidates.join(", ")
+            ),
+            BeanFactoryError::PropertyNotFound(key) => write!(f, "property {} not found", key),
+            BeanFactoryError::InvalidProperty { key, message } => {
+                write!(f, "invalid property {}: {}", key, message)
+            }
+            BeanFactoryError::PropertySource(message) => {
+                write!(f, "failed to load properties: {}", message)
+            }
+            BeanFactoryError::Initializing => write!(f, "bean factory is initializing"),
+            BeanFactoryError::InitCancelled => {
+                write!(f, "bean factory was shut down during init")
+            }
+            BeanFactoryError::Multiple(errors) => {
+                write!(f, "{} beans failed to initialize", errors.len())?;
+                for e in errors {
+                    write!(f, "; {}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for BeanFactoryError {}
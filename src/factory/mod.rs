@@ -1,16 +1,31 @@
-use std::{any::type_name, collections::HashMap, sync::Arc, vec};
+use std::{
+    any::TypeId,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    future::Future,
+    path::PathBuf,
+    sync::{Arc, Weak},
+    time::Duration,
+    vec,
+};
 
 use actix::prelude::*;
+use arc_swap::ArcSwap;
+use config::{Config, Environment, File};
 //use actix::dev::ToEnvelope;
 
+use self::error::BeanFactoryError;
 use self::model::{
-    BeanDefinition, BeanFactoryCmd, BeanFactoryResult, DynAny, FactoryData, FactoryEvent,
-    InitFactory, QueryBean,
+    ActorRestarted, AttachSnapshot, BeanCondition, BeanDefinition, BeanDependency, BeanFactoryCmd,
+    BeanFactoryResult, BeanFuture, BeanInstance, BeanKey, BeanScope, BoxError, DuplicatePolicy,
+    DynAny, FactoryData, FactoryEvent, InitFactory, NotifyFn, PropertySource, QueryBean,
+    ReplaceBean, ScopedBean, ShutdownFactory,
 };
 
+pub mod error;
 pub mod model;
 
-fn spawn_start(inner: BeanFactoryCore) -> Addr<BeanFactoryCore> {
+fn spawn_start(mut inner: BeanFactoryCore) -> Addr<BeanFactoryCore> {
+    inner.own_system = true;
     let (tx, rx) = std::sync::mpsc::sync_channel(1);
     std::thread::spawn(move || {
         let rt = System::new();
@@ -21,10 +36,49 @@ fn spawn_start(inner: BeanFactoryCore) -> Addr<BeanFactoryCore> {
     rx.recv().unwrap()
 }
 
+/// 初始化及关闭时等待每个actor处理事件的默认超时时间
+pub(crate) const DEFAULT_INIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+type InitResponse = ResponseActFuture<BeanFactoryCore, Result<FactoryData, BeanFactoryError>>;
+
+/// bean创建进度,遇到异步创建函数时返回等待中的future
+enum BuildProgress {
+    Done(Vec<BeanFactoryError>),
+    Pending(fut::LocalBoxActorFuture<BeanFactoryCore, Vec<BeanFactoryError>>),
+}
+
 #[derive(Default)]
 pub struct BeanFactoryCore {
-    bean_map: HashMap<String, Arc<DynAny>>,
-    bean_definition_map: HashMap<String, BeanDefinition>,
+    bean_map: FactoryData,
+    bean_definition_map: HashMap<BeanKey, BeanDefinition>,
+    init_order: Vec<BeanKey>,
+    /// 是否已完成初始化,关闭后重置
+    initialized: bool,
+    /// 是否正在初始化,初始化期间拒绝再次初始化及替换bean
+    initializing: bool,
+    /// 每次初始化及初始化期间关闭时递增,初始化的后续步骤发现变化时说明初始化已被取消
+    init_id: usize,
+    duplicate_policy: DuplicatePolicy,
+    /// 被后注册的同名bean覆盖的bean名称
+    overridden_names: Vec<String>,
+    /// 按`DuplicatePolicy::Reject`拒绝的注册,init时返回
+    duplicate_errors: Vec<BeanFactoryError>,
+    /// 等待actor处理注入及销毁事件的超时时间,为空时使用`DEFAULT_INIT_TIMEOUT`
+    init_timeout: Option<Duration>,
+    /// 是否运行在`spawn_start`创建的独立System中,关闭工厂时需要停止该System
+    own_system: bool,
+    /// 发布给`BeanFactory`的容器数据快照,同步获取bean时直接读取,不经过actor
+    snapshot: Arc<ArcSwap<FactoryData>>,
+    /// 通过`BeanFactory::new_by_core`创建的工厂持有的快照
+    attached_snapshots: Vec<Weak<ArcSwap<FactoryData>>>,
+    /// 激活的profile
+    active_profiles: Vec<String>,
+    /// 属性来源,初始化时按顺序加载
+    property_sources: Vec<PropertySource>,
+    /// 通过`BeanFactory::set_property`设置的属性,优先级高于属性来源
+    properties: HashMap<String, String>,
+    /// 带注册条件的bean,初始化前判断条件后再注册
+    conditional_beans: Vec<BeanDefinition>,
 }
 
 impl BeanFactoryCore {
@@ -32,74 +86,771 @@ impl BeanFactoryCore {
         spawn_start(self)
     }
 
-    fn init(&mut self) {
+    /// 发布当前容器数据的快照
+    fn publish(&mut self) {
+        let data = Arc::new(self.bean_map.clone());
+        self.snapshot.store(data.clone());
+        self.attached_snapshots.retain(|e| match e.upgrade() {
+            Some(snapshot) => {
+                snapshot.store(data.clone());
+                true
+            }
+            None => false,
+        });
+    }
+
+    fn register(&mut self, bean: BeanDefinition) {
+        let name = bean.bean_name().to_owned();
+        let mut entry = match self.bean_definition_map.entry(bean.key()) {
+            Entry::Vacant(e) => {
+                e.insert(bean);
+                return;
+            }
+            Entry::Occupied(e) => e,
+        };
+        match self.duplicate_policy {
+            DuplicatePolicy::Reject => {
+                log::error!("BeanFactory reject duplicate bean: {}", name);
+                self.duplicate_errors
+                    .push(BeanFactoryError::DuplicateRegistration(name));
+            }
+            DuplicatePolicy::Override => {
+                log::warn!("BeanFactory override bean: {}", name);
+                if !self.overridden_names.contains(&name) {
+                    self.overridden_names.push(name);
+                }
+                entry.insert(bean);
+            }
+            DuplicatePolicy::KeepFirst => {
+                log::info!("BeanFactory ignore duplicate bean: {}", name);
+            }
+        }
+    }
+
+    /// 是否满足profile及属性条件
+    fn matches_conditions(&self, bean: &BeanDefinition) -> bool {
+        if !bean.profiles.is_empty()
+            && !bean
+                .profiles
+                .iter()
+                .any(|e| self.active_profiles.contains(e))
+        {
+            return false;
+        }
+        bean.conditions.iter().all(|condition| match condition {
+            BeanCondition::OnMissingBean => true,
+            BeanCondition::OnProperty { key, value } => {
+                match (self.bean_map.get_property_opt::<String>(key), value) {
+                    (Some(v), Some(value)) => &v == value,
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                }
+            }
+        })
+    }
+
+    /// 按顺序加载属性来源,最后加载设置的属性
+    fn load_properties(&mut self) -> Result<(), BeanFactoryError> {
+        let mut builder = Config::builder();
+        for source in &self.property_sources {
+            builder = match source {
+                PropertySource::File(path) => builder.add_source(File::from(path.as_path())),
+                PropertySource::Env(prefix) => builder.add_source(
+                    Environment::with_prefix(prefix)
+                        .prefix_separator("_")
+                        .separator("__"),
+                ),
+            };
+        }
+        for (key, value) in &self.properties {
+            builder = builder
+                .set_override(key.as_str(), value.as_str())
+                .map_err(|e| BeanFactoryError::PropertySource(e.to_string()))?;
+        }
+        let properties = builder
+            .build()
+            .map_err(|e| BeanFactoryError::PropertySource(e.to_string()))?;
+        self.bean_map.set_properties(properties);
+        Ok(())
+    }
+
+    /// 判断带注册条件的bean,满足条件的bean加入注册列表
+    /// 先注册满足profile及属性条件的bean,再按注册顺序判断`OnMissingBean`条件
+    fn register_conditional_beans(&mut self) {
+        let beans = std::mem::take(&mut self.conditional_beans);
+        let (fallbacks, beans): (Vec<BeanDefinition>, Vec<BeanDefinition>) = beans
+            .into_iter()
+            .filter(|bean| {
+                let matched = self.matches_conditions(bean);
+                if !matched {
+                    log::info!(
+                        "BeanFactory skip bean {}: condition not matched",
+                        bean.bean_name()
+                    );
+                }
+                matched
+            })
+            .partition(|e| e.conditions.contains(&BeanCondition::OnMissingBean));
+        for bean in beans {
+            self.register(bean);
+        }
+        for bean in fallbacks {
+            let provides = bean
+                .provides
+                .iter()
+                .map(|e| (e.type_id, e.type_name.as_str()));
+            let exists = std::iter::once((bean.type_id, bean.type_name.as_str()))
+                .chain(provides)
+                .any(|(type_id, type_name)| {
+                    let dep = BeanDependency {
+                        name: type_name.to_owned(),
+                        type_id: Some(type_id),
+                        required: false,
+                        inject: false,
+                        multiple: true,
+                    };
+                    !self.resolve_dependency(&dep).is_empty() || self.bean_map.parent_contains(&dep)
+                });
+            if exists {
+                log::info!("BeanFactory skip bean {}: bean exists", bean.bean_name());
+                continue;
+            }
+            self.register(bean);
+        }
+    }
+
+    /// bean的展示名称
+    fn bean_name(&self, key: &BeanKey) -> String {
+        self.bean_definition_map[key].bean_name().to_owned()
+    }
+
+    /// 依赖对应的bean
+    /// 按类型声明的依赖优先匹配该类型未指定名称的bean,否则匹配该类型的所有bean;
+    /// 按名称声明的依赖优先匹配同名bean,否则把名称当作类型名称匹配
+    fn resolve_dependency(&self, dep: &BeanDependency) -> Vec<BeanKey> {
+        let key = match dep.type_id {
+            Some(type_id) => BeanKey::Type(type_id),
+            None => BeanKey::Name(dep.name.to_owned()),
+        };
+        if self.bean_definition_map.contains_key(&key) {
+            return vec![key];
+        }
+        let mut beans: Vec<(&BeanKey, &BeanDefinition)> = self
+            .bean_definition_map
+            .iter()
+            .filter(|(_, bean)| match dep.type_id {
+                Some(type_id) => bean.is_type_of(type_id),
+                None => bean.type_name == dep.name,
+            })
+            .collect();
+        beans.sort_by(|a, b| a.1.bean_name().cmp(b.1.bean_name()));
+        beans.into_iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    /// 按依赖关系排序bean,被依赖的bean排在前面
+    /// 未注册的依赖直接忽略;同一层级按名称排序,保证顺序稳定
+    /// 只用于注入的依赖尽量排在前面,会形成循环依赖时忽略,互相注入的actor不会形成循环依赖
+    /// 其它依赖存在循环依赖时返回完整的依赖链
+    fn sort_bean_names(&self) -> Result<Vec<BeanKey>, BeanFactoryError> {
+        let mut beans: Vec<(&BeanKey, &BeanDefinition)> = self.bean_definition_map.iter().collect();
+        beans.sort_by(|a, b| a.1.bean_name().cmp(b.1.bean_name()));
+        let mut edges: HashMap<BeanKey, Vec<BeanKey>> = HashMap::new();
+        for (key, bean) in &beans {
+            let deps = bean.depends_on.iter().filter(|e| !e.inject);
+            let dep_keys = deps.flat_map(|dep| self.resolve_dependency(dep)).collect();
+            edges.insert((*key).clone(), dep_keys);
+        }
+        self.sort_by_edges(&beans, &edges)?;
+        for (key, bean) in &beans {
+            for dep in bean.depends_on.iter().filter(|e| e.inject) {
+                for dep_key in self.resolve_dependency(dep) {
+                    if !Self::reaches(&edges, &dep_key, key) {
+                        edges.get_mut(*key).unwrap().push(dep_key);
+                    }
+                }
+            }
+        }
+        self.sort_by_edges(&beans, &edges)
+    }
+
+    /// 依赖关系中`from`是否直接或间接依赖`to`
+    fn reaches(edges: &HashMap<BeanKey, Vec<BeanKey>>, from: &BeanKey, to: &BeanKey) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![from];
+        while let Some(key) = stack.pop() {
+            if key == to {
+                return true;
+            }
+            if visited.insert(key) {
+                stack.extend(edges.get(key).into_iter().flatten());
+            }
+        }
+        false
+    }
+
+    fn sort_by_edges(
+        &self,
+        beans: &[(&BeanKey, &BeanDefinition)],
+        edges: &HashMap<BeanKey, Vec<BeanKey>>,
+    ) -> Result<Vec<BeanKey>, BeanFactoryError> {
+        let mut visited = HashSet::new();
+        let mut path = vec![];
+        let mut order = Vec::with_capacity(beans.len());
+        for (key, _) in beans {
+            self.visit_bean(key, edges, &mut visited, &mut path, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    fn visit_bean(
+        &self,
+        key: &BeanKey,
+        edges: &HashMap<BeanKey, Vec<BeanKey>>,
+        visited: &mut HashSet<BeanKey>,
+        path: &mut Vec<BeanKey>,
+        order: &mut Vec<BeanKey>,
+    ) -> Result<(), BeanFactoryError> {
+        if let Some(index) = path.iter().position(|e| e == key) {
+            let mut chain: Vec<String> = path[index..].iter().map(|e| self.bean_name(e)).collect();
+            chain.push(self.bean_name(key));
+            return Err(BeanFactoryError::CircularDependency(chain));
+        }
+        if visited.contains(key) {
+            return Ok(());
+        }
+        if let Some(dep_keys) = edges.get(key) {
+            path.push(key.clone());
+            for dep_key in dep_keys {
+                self.visit_bean(dep_key, edges, visited, path, order)?;
+            }
+            path.pop();
+            order.push(key.clone());
+        }
+        visited.insert(key.clone());
+        Ok(())
+    }
+
+    /// 未注册的必须依赖
+    fn check_required_dependencies(&self) -> Vec<BeanFactoryError> {
+        self.init_order
+            .iter()
+            .flat_map(|key| self.missing_dependencies(key))
+            .collect()
+    }
+
+    /// bean`key`未注册的必须依赖
+    fn missing_dependencies(&self, key: &BeanKey) -> Vec<BeanFactoryError> {
+        let bean = &self.bean_definition_map[key];
+        bean.depends_on
+            .iter()
+            .filter(|dep| dep.required)
+            .filter(|dep| {
+                self.resolve_dependency(dep).is_empty() && !self.bean_map.parent_contains(dep)
+            })
+            .map(|dep| BeanFactoryError::MissingDependency {
+                bean: bean.bean_name().to_owned(),
+                dependency: dep.name.to_owned(),
+            })
+            .collect()
+    }
+
+    /// 无法注入的注入依赖,注入依赖不保证先创建,所有bean创建后再检查
+    /// 按注入时的查找规则检查:必须依赖不存在时返回`MissingDependency`;
+    /// 按类型注入单个bean时有多个候选bean且无法选出唯一的bean,不论是否必须都返回`AmbiguousBean`
+    fn check_inject_dependencies(&self) -> Vec<BeanFactoryError> {
+        let mut errors = vec![];
+        for key in &self.init_order {
+            let bean = &self.bean_definition_map[key];
+            let deps = bean.depends_on.iter().filter(|e| e.inject);
+            for dep in deps.filter(|e| e.required || !e.multiple) {
+                let result = match dep.type_id {
+                    _ if dep.multiple => {
+                        let keys = self.resolve_dependency(dep);
+                        if keys.iter().all(|e| self.bean_map.contains(e))
+                            && (!keys.is_empty() || self.bean_map.parent_contains(dep))
+                        {
+                            Ok(())
+                        } else {
+                            Err(BeanFactoryError::BeanNotFound(dep.name.to_owned()))
+                        }
+                    }
+                    Some(type_id) => self.bean_map.check_by_type(type_id, &dep.name),
+                    None => self.bean_map.check_by_name(&dep.name),
+                };
+                match result {
+                    Ok(_) => {}
+                    Err(BeanFactoryError::BeanNotFound(_)) if !dep.required => {}
+                    Err(BeanFactoryError::BeanNotFound(_)) => {
+                        errors.push(BeanFactoryError::MissingDependency {
+                            bean: bean.bean_name().to_owned(),
+                            dependency: dep.name.to_owned(),
+                        });
+                    }
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        errors
+    }
+
+    /// 初始化前检查,返回已有的注册错误
+    /// 存在循环依赖或必须依赖未注册时直接返回错误,不创建任何bean
+    fn prepare_init(&mut self) -> Result<Vec<BeanFactoryError>, BeanFactoryError> {
         log::info!("BeanFactory start init ...");
-        for (name, bean) in &self.bean_definition_map {
-            match &bean.provider {
-                model::Provieder::Fn(f) => {
-                    if let Some(v) = f() {
-                        self.bean_map.insert(bean.type_name.to_owned(), v);
-                        log::info!("BeanFactory init bean by fn: {}",name);
+        self.load_properties()?;
+        self.register_conditional_beans();
+        self.init_order = self.sort_bean_names()?;
+        let mut errors = std::mem::take(&mut self.duplicate_errors);
+        let missing_errors = self.check_required_dependencies();
+        if !missing_errors.is_empty() {
+            errors.extend(missing_errors);
+            return Err(BeanFactoryError::from_errors(errors));
+        }
+        Ok(errors)
+    }
+
+    /// 从`index`开始按依赖顺序创建bean
+    /// 遇到异步创建函数时返回等待中的future,future完成后继续创建后续的bean
+    /// 创建失败的bean不影响其它bean,所有失败的bean统一返回
+    /// 等待异步创建函数期间初始化被取消时,不再保存该bean及创建后续的bean
+    fn build_beans(
+        &mut self,
+        mut index: usize,
+        mut errors: Vec<BeanFactoryError>,
+        init_id: usize,
+    ) -> BuildProgress {
+        while index < self.init_order.len() {
+            let key = self.init_order[index].clone();
+            index += 1;
+            if let Some(fut) = self.build_bean(&key, &mut errors) {
+                let fut = fut.into_actor(self).then(move |v, act, _ctx| {
+                    let mut errors = errors;
+                    if act.init_id != init_id {
+                        return fut::ready(errors).boxed_local();
                     }
+                    act.on_async_bean_built(&key, v, &mut errors);
+                    match act.build_beans(index, errors, init_id) {
+                        BuildProgress::Done(errors) => fut::ready(errors).boxed_local(),
+                        BuildProgress::Pending(fut) => fut,
+                    }
+                });
+                self.publish();
+                return BuildProgress::Pending(Box::pin(fut));
+            }
+        }
+        self.publish();
+        BuildProgress::Done(errors)
+    }
+
+    /// 创建bean,异步创建函数返回需要等待的future
+    fn build_bean(
+        &mut self,
+        key: &BeanKey,
+        errors: &mut Vec<BeanFactoryError>,
+    ) -> Option<BeanFuture> {
+        let bean = &self.bean_definition_map[key];
+        let name = bean.bean_name();
+        let failed_dep = bean
+            .depends_on
+            .iter()
+            .filter(|e| e.required && !e.inject)
+            .find(|dep| {
+                self.resolve_dependency(dep)
+                    .iter()
+                    .any(|e| !self.bean_map.contains(e))
+            });
+        if let Some(dep) = failed_dep {
+            errors.push(BeanFactoryError::MissingDependency {
+                bean: name.to_owned(),
+                dependency: dep.name.to_owned(),
+            });
+            return None;
+        }
+        match (&bean.provider, bean.scope) {
+            (model::Provieder::AsyncFn(f), scope) => {
+                if bean.lazy || scope != BeanScope::Singleton {
+                    log::warn!(
+                        "BeanFactory async bean {} is created as eager singleton",
+                        name
+                    );
+                }
+                log::info!("BeanFactory init bean by async fn: {}", name);
+                return Some(f(self.bean_map.clone()));
+            }
+            (model::Provieder::Fn(f), BeanScope::Prototype) => {
+                let scoped = ScopedBean::new(name, f.clone(), bean.notify.clone());
+                let instance = BeanInstance::Prototype(scoped);
+                self.bean_map.insert(bean, instance);
+                log::info!("BeanFactory init prototype bean: {}", name);
+            }
+            (model::Provieder::Fn(f), BeanScope::Arbiter) => {
+                let scoped = ScopedBean::new(name, f.clone(), bean.notify.clone());
+                let instance = BeanInstance::Arbiter(scoped, Default::default());
+                self.bean_map.insert(bean, instance);
+                log::info!("BeanFactory init arbiter scope bean: {}", name);
+            }
+            (model::Provieder::Fn(f), BeanScope::Singleton) if bean.lazy => {
+                let scoped = ScopedBean::new(name, f.clone(), bean.notify.clone());
+                let instance = BeanInstance::Lazy(scoped, Default::default());
+                self.bean_map.insert(bean, instance);
+                log::info!("BeanFactory init lazy bean: {}", name);
+            }
+            (model::Provieder::Fn(f), BeanScope::Singleton) => match f(&self.bean_map) {
+                Ok(v) => {
+                    self.bean_map.insert(bean, BeanInstance::Singleton(v));
+                    log::info!("BeanFactory init bean by fn: {}", name);
                 }
-                model::Provieder::Value(v) => {
-                    self.bean_map.insert(bean.type_name.to_owned(), v.clone());
-                    log::info!("BeanFactory init bean value: {}",name);
+                Err(e) => {
+                    log::error!("BeanFactory init bean {} error: {}", name, e);
+                    errors.push(BeanFactoryError::ProviderFailed {
+                        bean: name.to_owned(),
+                        message: e.to_string(),
+                    });
                 }
+            },
+            (model::Provieder::Value(v), _) => {
+                let instance = BeanInstance::Singleton(v.clone());
+                self.bean_map.insert(bean, instance);
+                log::info!("BeanFactory init bean value: {}", name);
             }
         }
+        None
     }
 
-    /*
-    fn do_notify<T>(c:Arc<DynAny>,event: FactoryEvent)
-    where T: Actor<Context = Context<T>> + Handler<FactoryEvent>,
-        <T as Actor>::Context: AsyncContext<T> + ToEnvelope<T,FactoryEvent>
-    {
-        c.downcast::<Addr<T>>().ok().map(|e|e.do_send(event));
+    fn on_async_bean_built(
+        &mut self,
+        key: &BeanKey,
+        v: Result<Arc<DynAny>, BoxError>,
+        errors: &mut Vec<BeanFactoryError>,
+    ) {
+        let bean = &self.bean_definition_map[key];
+        let name = bean.bean_name();
+        match v {
+            Ok(v) => {
+                self.bean_map.insert(bean, BeanInstance::Singleton(v));
+                log::info!("BeanFactory init bean by async fn complete: {}", name);
+            }
+            Err(e) => {
+                log::error!("BeanFactory init bean {} error: {}", name, e);
+                errors.push(BeanFactoryError::ProviderFailed {
+                    bean: name.to_owned(),
+                    message: e.to_string(),
+                });
+            }
+        }
     }
-    */
 
-    fn do_notify_event(&mut self, event: FactoryEvent) {
-        for (name, bean) in &self.bean_definition_map {
-            /*
-            if !bean.inject {
-                continue;
+    fn finish_init(
+        &mut self,
+        mut errors: Vec<BeanFactoryError>,
+        init_id: usize,
+        ctx: &mut Context<Self>,
+    ) -> InitResponse {
+        if self.init_id != init_id {
+            log::error!("BeanFactory init cancelled by shutdown");
+            return Box::pin(fut::ready(Err(BeanFactoryError::InitCancelled)));
+        }
+        errors.extend(self.check_inject_dependencies());
+        if !errors.is_empty() {
+            self.initializing = false;
+            let e = BeanFactoryError::from_errors(errors);
+            log::error!("BeanFactory init error: {}", e);
+            return Box::pin(fut::ready(Err(e)));
+        }
+        self.inject(init_id, ctx)
+    }
+
+    /// 初始化工厂
+    /// 同步创建函数在当前消息中直接执行;异步创建函数在actor中等待,不阻塞其它消息
+    /// 初始化期间再次初始化或替换bean返回`Initializing`;关闭工厂会取消初始化,初始化返回`InitCancelled`
+    fn init(&mut self, ctx: &mut Context<Self>) -> InitResponse {
+        if self.initializing {
+            log::error!("BeanFactory is initializing");
+            return Box::pin(fut::ready(Err(BeanFactoryError::Initializing)));
+        }
+        self.initializing = true;
+        self.init_id += 1;
+        let init_id = self.init_id;
+        let errors = match self.prepare_init() {
+            Ok(errors) => errors,
+            Err(e) => {
+                self.initializing = false;
+                log::error!("BeanFactory init error: {}", e);
+                return Box::pin(fut::ready(Err(e)));
+            }
+        };
+        match self.build_beans(0, errors, init_id) {
+            BuildProgress::Done(errors) => self.finish_init(errors, init_id, ctx),
+            BuildProgress::Pending(fut) => {
+                Box::pin(fut.then(move |errors, act, ctx| act.finish_init(errors, init_id, ctx)))
             }
-            */
-            //self.bean_map.get(name).map(|e| Self::do_notify2(e.clone(), event));
-            match (self.bean_map.get(name), bean.notify.as_ref()) {
-                (Some(c), Some(notify)) => {
-                    notify(c.clone(), event.clone());
-                    match &event {
-                        FactoryEvent::Inject { factory : _, factory_data : _ } => {
-                            log::info!("BeanFactory trigger inject, bean: {}",name);
-                        },
-                        FactoryEvent::Complete => {},
+        }
+    }
+
+    /// 按初始化顺序逐个通知actor,等待actor处理完事件后再通知下一个
+    /// 返回超时或已停止的actor名称
+    fn notify_events(
+        &self,
+        keys: &[BeanKey],
+        events: Vec<FactoryEvent>,
+    ) -> impl Future<Output = Vec<String>> {
+        let targets: Vec<(String, Arc<DynAny>, NotifyFn)> = keys
+            .iter()
+            .filter_map(|key| {
+                let bean = self.bean_map.get_singleton(key)?;
+                let notify = self.bean_definition_map[key].notify.clone()?;
+                Some((self.bean_name(key), bean, notify))
+            })
+            .collect();
+        let timeout = self.init_timeout.unwrap_or(DEFAULT_INIT_TIMEOUT);
+        async move {
+            let mut no_response = vec![];
+            for event in events {
+                for (name, bean, notify) in &targets {
+                    if no_response.contains(name) {
+                        continue;
                     }
-                },
-                (_, _) => {}
+                    if let FactoryEvent::Inject { .. } = &event {
+                        log::info!("BeanFactory trigger inject, bean: {}", name);
+                    }
+                    let fut = match notify(bean.clone(), event.clone(), true) {
+                        Some(fut) => fut,
+                        None => continue,
+                    };
+                    match actix::clock::timeout(timeout, fut).await {
+                        Ok(Ok(_)) => {}
+                        Ok(Err(e)) => {
+                            log::error!("BeanFactory notify bean {} error: {}", name, e);
+                            no_response.push(name.to_owned());
+                        }
+                        Err(_) => {
+                            log::error!("BeanFactory notify bean {} timeout", name);
+                            no_response.push(name.to_owned());
+                        }
+                    }
+                }
+            }
+            no_response
+        }
+    }
+
+    /// 触发依赖注入,等待所有actor处理完注入及完成事件
+    fn inject(&mut self, init_id: usize, ctx: &mut Context<Self>) -> InitResponse {
+        let factory_data = self.bean_map.clone();
+        let inject_event = FactoryEvent::Inject {
+            factory: BeanFactory::with_snapshot(ctx.address(), self.snapshot.clone()),
+            factory_data: factory_data.clone(),
+        };
+        let fut = self.notify_events(&self.init_order, vec![inject_event, FactoryEvent::Complete]);
+        Box::pin(fut.into_actor(self).map(move |no_response, act, _ctx| {
+            if act.init_id != init_id {
+                log::error!("BeanFactory init cancelled by shutdown");
+                return Err(BeanFactoryError::InitCancelled);
+            }
+            act.initializing = false;
+            if !no_response.is_empty() {
+                let e = BeanFactoryError::InjectTimeout(no_response);
+                log::error!("BeanFactory init error: {}", e);
+                return Err(e);
             }
+            act.initialized = true;
+            log::info!("BeanFactory complete initialization");
+            Ok(factory_data)
+        }))
+    }
+
+    /// 运行时替换bean
+    /// 工厂未初始化时只替换定义;已初始化时重新创建bean,创建失败时保留原来的bean,
+    /// 原来没有的bean创建后加入容器
+    fn replace(&mut self, bean: BeanDefinition) -> InitResponse {
+        if self.initializing {
+            log::error!("BeanFactory is initializing");
+            return Box::pin(fut::ready(Err(BeanFactoryError::Initializing)));
         }
+        let key = bean.key();
+        let is_new = !self.init_order.contains(&key);
+        if is_new && !self.initialized {
+            log::info!("BeanFactory replace bean definition: {}", bean.bean_name());
+            self.bean_definition_map.insert(key, bean);
+            return Box::pin(fut::ready(Ok(self.bean_map.clone())));
+        }
+        log::info!("BeanFactory replace bean: {}", bean.bean_name());
+        let old_bean = self.bean_map.get_created(&key);
+        let old_definition = self.bean_definition_map.insert(key.clone(), bean);
+        let old_map = self.bean_map.clone();
+        let mut errors = self.missing_dependencies(&key);
+        let built = if errors.is_empty() {
+            self.build_bean(&key, &mut errors)
+        } else {
+            None
+        };
+        let built = match built {
+            Some(fut) => {
+                let key = key.clone();
+                fut.into_actor(self)
+                    .map(move |v, act, _ctx| {
+                        let mut errors = errors;
+                        act.on_async_bean_built(&key, v, &mut errors);
+                        errors
+                    })
+                    .boxed_local()
+            }
+            None => fut::ready(errors).boxed_local(),
+        };
+        Box::pin(built.then(move |errors, act, ctx| {
+            if !errors.is_empty() {
+                act.bean_map = old_map;
+                match old_definition {
+                    Some(definition) => act.bean_definition_map.insert(key, definition),
+                    None => act.bean_definition_map.remove(&key),
+                };
+                let e = BeanFactoryError::from_errors(errors);
+                log::error!("BeanFactory replace bean error: {}", e);
+                return fut::ready(Err(e)).boxed_local();
+            }
+            if is_new {
+                act.init_order.push(key.clone());
+            }
+            act.refresh(key, old_bean.zip(old_definition), ctx)
+        }))
     }
 
-    fn inject(&mut self, ctx: &mut Context<Self>) -> FactoryData {
-        let factory_data = FactoryData(Arc::new(self.bean_map.clone()));
+    /// 发布替换后的容器数据,注入替换的bean,并重新注入直接依赖它的actor
+    /// 最后销毁被替换的bean
+    fn refresh(
+        &mut self,
+        key: BeanKey,
+        old: Option<(Arc<DynAny>, BeanDefinition)>,
+        ctx: &mut Context<Self>,
+    ) -> InitResponse {
+        self.publish();
+        let dependents: Vec<BeanKey> = self
+            .init_order
+            .iter()
+            .filter(|e| {
+                self.bean_definition_map[*e]
+                    .depends_on
+                    .iter()
+                    .any(|dep| self.resolve_dependency(dep).contains(&key))
+            })
+            .cloned()
+            .collect();
+        let factory_data = self.bean_map.clone();
         let inject_event = FactoryEvent::Inject {
-            factory: BeanFactory::new_by_core(ctx.address()),
+            factory: BeanFactory::with_snapshot(ctx.address(), self.snapshot.clone()),
             factory_data: factory_data.clone(),
         };
-        let complete_event = FactoryEvent::Complete;
-        self.do_notify_event(inject_event);
-        self.do_notify_event(complete_event);
-        log::info!("BeanFactory complete initialization");
-        factory_data
+        let refreshed_event = FactoryEvent::Refreshed {
+            bean: self.bean_name(&key),
+        };
+        let replaced = self.notify_events(
+            std::slice::from_ref(&key),
+            vec![inject_event.clone(), FactoryEvent::Complete],
+        );
+        let refreshed = self.notify_events(&dependents, vec![inject_event, refreshed_event]);
+        let fut = async move {
+            let mut no_response = replaced.await;
+            no_response.extend(refreshed.await);
+            no_response
+        };
+        Box::pin(fut.into_actor(self).map(move |no_response, _act, _ctx| {
+            if let Some((bean, definition)) = old {
+                log::info!(
+                    "BeanFactory destroy replaced bean: {}",
+                    definition.bean_name()
+                );
+                if let Some(destroy) = definition.destroy.as_ref() {
+                    destroy(bean.clone());
+                }
+                if let Some(notify) = definition.notify.as_ref() {
+                    notify(bean, FactoryEvent::Destroy, false);
+                }
+            }
+            if !no_response.is_empty() {
+                let e = BeanFactoryError::InjectTimeout(no_response);
+                log::error!("BeanFactory refresh bean error: {}", e);
+                return Err(e);
+            }
+            Ok(factory_data)
+        }))
+    }
+
+    /// 按初始化的逆序销毁已创建的单例bean
+    /// actor发送销毁事件并等待其停止,普通bean调用销毁回调
+    /// 正在初始化时取消初始化
+    fn shutdown(&mut self) -> ResponseActFuture<Self, Result<(), BeanFactoryError>> {
+        if self.initializing {
+            log::warn!("BeanFactory shutdown during init");
+            self.initializing = false;
+            self.init_id += 1;
+        }
+        let targets: Vec<(String, Arc<DynAny>, BeanDefinition)> = self
+            .init_order
+            .iter()
+            .rev()
+            .filter_map(|key| {
+                let bean = self.bean_map.get_created(key)?;
+                let definition = self.bean_definition_map[key].clone();
+                Some((definition.bean_name().to_owned(), bean, definition))
+            })
+            .collect();
+        //先发布空的容器数据,工厂不再持有actor地址,受监督及SyncArbiter中的actor才会停止
+        self.bean_map = self.bean_map.cleared();
+        self.init_order.clear();
+        self.initialized = false;
+        self.publish();
+        let timeout = self.init_timeout.unwrap_or(DEFAULT_INIT_TIMEOUT);
+        let fut = async move {
+            let mut no_response = vec![];
+            for (name, bean, definition) in targets {
+                log::info!("BeanFactory destroy bean: {}", &name);
+                if let Some(destroy) = definition.destroy.as_ref() {
+                    destroy(bean.clone());
+                }
+                let fut = match definition
+                    .notify
+                    .as_ref()
+                    .and_then(|notify| notify(bean, FactoryEvent::Destroy, true))
+                {
+                    Some(fut) => fut,
+                    None => continue,
+                };
+                match actix::clock::timeout(timeout, fut).await {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => {
+                        log::error!("BeanFactory destroy bean {} error: {}", &name, e);
+                        no_response.push(name);
+                    }
+                    Err(_) => {
+                        log::error!("BeanFactory destroy bean {} timeout", &name);
+                        no_response.push(name);
+                    }
+                }
+            }
+            no_response
+        };
+        Box::pin(fut.into_actor(self).map(|no_response, act, _ctx| {
+            if act.own_system {
+                System::current().stop();
+            }
+            if !no_response.is_empty() {
+                let e = BeanFactoryError::DestroyTimeout(no_response);
+                log::error!("BeanFactory shutdown error: {}", e);
+                return Err(e);
+            }
+            log::info!("BeanFactory complete shutdown");
+            Ok(())
+        }))
     }
 }
 
 impl Actor for BeanFactoryCore {
     type Context = Context<Self>;
 
-    fn started(&mut self, _ctx: &mut Self::Context) {
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.bean_map
+            .set_factory(ctx.address().downgrade(), &self.snapshot);
+        self.publish();
         log::info!("BeanFactoryCore started")
     }
 }
@@ -107,18 +858,65 @@ impl Actor for BeanFactoryCore {
 impl Handler<BeanDefinition> for BeanFactoryCore {
     type Result = ();
     fn handle(&mut self, msg: BeanDefinition, _ctx: &mut Self::Context) -> Self::Result {
-        self.bean_definition_map
-            .insert(msg.type_name.to_owned(), msg);
+        if msg.is_conditional() {
+            self.conditional_beans.push(msg);
+        } else {
+            self.register(msg);
+        }
     }
 }
 
 impl Handler<InitFactory> for BeanFactoryCore {
-    type Result = Option<FactoryData>;
+    type Result = InitResponse;
 
     fn handle(&mut self, _msg: InitFactory, ctx: &mut Self::Context) -> Self::Result {
-        self.init();
-        let factory_data = self.inject(ctx);
-        Some(factory_data)
+        self.init(ctx)
+    }
+}
+
+impl Handler<ShutdownFactory> for BeanFactoryCore {
+    type Result = ResponseActFuture<Self, Result<(), BeanFactoryError>>;
+
+    fn handle(&mut self, _msg: ShutdownFactory, _ctx: &mut Self::Context) -> Self::Result {
+        self.shutdown()
+    }
+}
+
+impl Handler<ReplaceBean> for BeanFactoryCore {
+    type Result = InitResponse;
+
+    fn handle(&mut self, msg: ReplaceBean, _ctx: &mut Self::Context) -> Self::Result {
+        self.replace(msg.0)
+    }
+}
+
+impl Handler<ActorRestarted> for BeanFactoryCore {
+    type Result = ();
+
+    fn handle(&mut self, msg: ActorRestarted, ctx: &mut Self::Context) -> Self::Result {
+        let key = self.init_order.iter().find(|key| {
+            self.bean_definition_map[*key].type_id == msg.type_id
+                && self
+                    .bean_map
+                    .get_created(key)
+                    .is_some_and(|v| (msg.matches)(v.as_ref()))
+        });
+        if let Some(key) = key.cloned() {
+            log::warn!("BeanFactory actor restarted: {}", self.bean_name(&key));
+            let fut = self.refresh(key, None, ctx).map(|_, _, _| ());
+            ctx.spawn(fut);
+        }
+    }
+}
+
+impl Handler<AttachSnapshot> for BeanFactoryCore {
+    type Result = ();
+
+    fn handle(&mut self, msg: AttachSnapshot, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(snapshot) = msg.0.upgrade() {
+            snapshot.store(Arc::new(self.bean_map.clone()));
+            self.attached_snapshots.push(msg.0);
+        }
     }
 }
 
@@ -126,7 +924,7 @@ impl Handler<QueryBean> for BeanFactoryCore {
     type Result = Option<Arc<DynAny>>;
 
     fn handle(&mut self, msg: QueryBean, _ctx: &mut Self::Context) -> Self::Result {
-        self.bean_map.get(&msg.0).map(|e| e.clone())
+        self.bean_map.get(&msg.0)
     }
 }
 
@@ -136,23 +934,48 @@ impl Handler<BeanFactoryCmd> for BeanFactoryCore {
     fn handle(&mut self, msg: BeanFactoryCmd, ctx: &mut Self::Context) -> Self::Result {
         match msg {
             BeanFactoryCmd::Init => {
-                self.init();
-                self.inject(ctx);
+                let fut = self.init(ctx).map(|_, _, _| ());
+                ctx.spawn(fut);
                 Some(BeanFactoryResult::None)
             }
             BeanFactoryCmd::QueryBean(name) => {
-                let v = self.bean_map.get(&name).map(|e| e.clone());
+                let v = self.bean_map.get(&name);
                 Some(BeanFactoryResult::Bean(v))
             }
             BeanFactoryCmd::QueryBeanNames => {
                 let v = self
                     .bean_definition_map
-                    .keys()
-                    .into_iter()
-                    .cloned()
+                    .values()
+                    .map(|e| e.bean_name().to_owned())
                     .collect();
                 Some(BeanFactoryResult::BeanNames(v))
             }
+            BeanFactoryCmd::QueryFactoryData => {
+                Some(BeanFactoryResult::FactoryData(self.bean_map.clone()))
+            }
+            BeanFactoryCmd::QueryOverriddenBeanNames => {
+                Some(BeanFactoryResult::BeanNames(self.overridden_names.clone()))
+            }
+            BeanFactoryCmd::SetDuplicatePolicy(policy) => {
+                self.duplicate_policy = policy;
+                Some(BeanFactoryResult::None)
+            }
+            BeanFactoryCmd::SetInitTimeout(timeout) => {
+                self.init_timeout = Some(timeout);
+                Some(BeanFactoryResult::None)
+            }
+            BeanFactoryCmd::SetActiveProfiles(profiles) => {
+                self.active_profiles = profiles;
+                Some(BeanFactoryResult::None)
+            }
+            BeanFactoryCmd::AddPropertySource(source) => {
+                self.property_sources.push(source);
+                Some(BeanFactoryResult::None)
+            }
+            BeanFactoryCmd::SetProperty(key, value) => {
+                self.properties.insert(key, value);
+                Some(BeanFactoryResult::None)
+            }
         }
     }
 }
@@ -160,25 +983,69 @@ impl Handler<BeanFactoryCmd> for BeanFactoryCore {
 #[derive(Clone)]
 pub struct BeanFactory {
     pub core_addr: Addr<BeanFactoryCore>,
+    /// BeanFactoryCore发布的容器数据快照
+    snapshot: Arc<ArcSwap<FactoryData>>,
+}
+
+impl Default for BeanFactory {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BeanFactory {
     /// 在actic环境下创建BeanFactory
     pub fn new() -> Self {
+        let core = BeanFactoryCore::default();
+        let snapshot = core.snapshot.clone();
         BeanFactory {
-            core_addr: BeanFactoryCore::start_default(),
+            core_addr: core.start(),
+            snapshot,
         }
     }
 
     /// 在普通环境下，在一个新建的actix线程创建BeanFactoryCore,再创建BeanFactory
     pub fn spawn_new() -> Self {
+        let core = BeanFactoryCore::default();
+        let snapshot = core.snapshot.clone();
+        BeanFactory {
+            core_addr: core.spawn_start(),
+            snapshot,
+        }
+    }
+
+    /// 在actix环境下创建子工厂
+    /// 子工厂找不到的bean到当前工厂查找,子工厂的bean可以依赖、注入当前工厂的bean
+    /// 依赖当前工厂bean的子工厂需要在当前工厂初始化后再初始化;关闭子工厂不影响当前工厂
+    pub fn new_child(&self) -> Self {
+        let mut core = BeanFactoryCore::default();
+        core.bean_map.set_parent(self.snapshot.clone());
+        let snapshot = core.snapshot.clone();
         BeanFactory {
-            core_addr: BeanFactoryCore::default().spawn_start(),
+            core_addr: core.start(),
+            snapshot,
         }
     }
 
+    /// 通过已启动的BeanFactoryCore创建BeanFactory
+    /// BeanFactoryCore处理完订阅消息后,同步获取bean的方法才能读取到快照
     pub fn new_by_core(core_addr: Addr<BeanFactoryCore>) -> Self {
-        Self { core_addr }
+        let snapshot: Arc<ArcSwap<FactoryData>> = Default::default();
+        core_addr.do_send(AttachSnapshot(Arc::downgrade(&snapshot)));
+        Self {
+            core_addr,
+            snapshot,
+        }
+    }
+
+    pub(crate) fn with_snapshot(
+        core_addr: Addr<BeanFactoryCore>,
+        snapshot: Arc<ArcSwap<FactoryData>>,
+    ) -> Self {
+        Self {
+            core_addr,
+            snapshot,
+        }
     }
 
     /// 注册bean
@@ -187,20 +1054,106 @@ impl BeanFactory {
         self.core_addr.do_send(bean);
     }
 
+    /// 设置同名bean重复注册的处理策略,只影响之后注册的bean
+    pub fn set_duplicate_policy(&self, policy: DuplicatePolicy) {
+        self.core_addr
+            .do_send(BeanFactoryCmd::SetDuplicatePolicy(policy));
+    }
+
+    /// 设置初始化时等待每个actor处理注入事件的超时时间,默认30秒
+    pub fn set_init_timeout(&self, timeout: Duration) {
+        self.core_addr
+            .do_send(BeanFactoryCmd::SetInitTimeout(timeout));
+    }
+
+    /// 设置激活的profile,`init`时只注册声明了激活profile或没有声明profile的bean
+    pub fn set_active_profiles(&self, profiles: &[&str]) {
+        let profiles = profiles.iter().map(|e| e.to_string()).collect();
+        self.core_addr
+            .do_send(BeanFactoryCmd::SetActiveProfiles(profiles));
+    }
+
+    /// 添加TOML或JSON配置文件作为属性来源,按添加顺序加载,后加载的覆盖先加载的
+    /// 文件不存在或格式错误时`init`返回错误
+    pub fn add_property_file(&self, path: impl Into<PathBuf>) {
+        self.core_addr
+            .do_send(BeanFactoryCmd::AddPropertySource(PropertySource::File(
+                path.into(),
+            )));
+    }
+
+    /// 添加前缀为`prefix`的环境变量作为属性来源,层级之间用`__`分隔,
+    /// 如前缀`APP`时`APP_DB__URL`对应属性`db.url`,`APP_DB__MAX_SIZE`对应`db.max_size`
+    pub fn add_env_properties(&self, prefix: &str) {
+        self.core_addr
+            .do_send(BeanFactoryCmd::AddPropertySource(PropertySource::Env(
+                prefix.to_owned(),
+            )));
+    }
+
+    /// 设置属性,优先级高于配置文件及环境变量
+    /// `init`时加载,bean通过`FactoryData::get_property`获取,也用于判断bean的`on_property`条件
+    pub fn set_property(&self, key: &str, value: &str) {
+        self.core_addr.do_send(BeanFactoryCmd::SetProperty(
+            key.to_owned(),
+            value.to_owned(),
+        ));
+    }
+
     /// 初始化工厂
     /// 创建bean实例
     /// 并触发依赖注入
-    /// 并等待返回容器数据
-    pub async fn init(&self) -> FactoryData {
+    /// 等待所有actor处理完注入事件后返回容器数据
+    /// 存在循环依赖或必须依赖未注册时返回错误,不创建任何bean
+    /// 有bean创建失败时返回所有失败的bean,不触发依赖注入
+    /// 有actor在超时时间内没有处理完注入事件时返回这些actor
+    /// 正在初始化时返回`Initializing`;初始化期间关闭工厂时返回`InitCancelled`
+    pub async fn init(&self) -> Result<FactoryData, BeanFactoryError> {
         match self.core_addr.send(InitFactory).await {
-            Ok(resp) => resp.unwrap(),
-            Err(_) => panic!("bean factory async init error!")
+            Ok(resp) => resp,
+            Err(_) => Err(BeanFactoryError::MailboxClosed),
         }
     }
 
+    /// 关闭工厂
+    /// 按初始化的逆序销毁已创建的单例bean:actor收到`FactoryEvent::Destroy`后停止,等待其停止后再销毁下一个;
+    /// 普通bean调用`BeanDefinition::on_destroy`设置的回调
+    /// 受监督的actor在还有地址时会被重启,调用方需要在关闭前释放持有的地址及`FactoryData`,
+    /// 否则返回`BeanFactoryError::DestroyTimeout`
+    /// 初始化期间关闭会取消初始化,之后创建完成的异步bean不再加入容器
+    /// 通过`spawn_new`创建的工厂,最后会停止工厂所在的System
+    pub async fn shutdown(&self) -> Result<(), BeanFactoryError> {
+        match self.core_addr.send(ShutdownFactory).await {
+            Ok(resp) => resp,
+            Err(_) => Err(BeanFactoryError::MailboxClosed),
+        }
+    }
+
+    /// 运行时替换同名bean
+    /// 重新创建bean并发布新的容器数据,替换的actor收到注入事件;
+    /// 直接依赖该bean的actor重新收到`FactoryEvent::Inject`,之后收到`FactoryEvent::Refreshed`
+    /// 依赖该bean的普通bean不会重新创建;被替换的bean最后销毁
+    /// 已初始化的工厂中原来没有的bean直接创建并加入容器;创建失败时保留原来的bean并返回错误
+    /// 正在初始化时返回`BeanFactoryError::Initializing`
+    pub async fn replace(&self, bean: BeanDefinition) -> Result<FactoryData, BeanFactoryError> {
+        match self.core_addr.send(ReplaceBean(bean)).await {
+            Ok(resp) => resp,
+            Err(_) => Err(BeanFactoryError::MailboxClosed),
+        }
+    }
+
+    /// 通知工厂受监督的actor已重启,在`Supervised::restarting`中调用
+    /// 工厂重新注入该actor,并向依赖它的actor重新发送`FactoryEvent::Inject`及`FactoryEvent::Refreshed`
+    pub fn notify_restarted<T: Actor>(&self, addr: Addr<T>) {
+        self.core_addr.do_send(ActorRestarted {
+            type_id: TypeId::of::<T>(),
+            matches: Box::new(move |v| v.downcast_ref::<Addr<T>>() == Some(&addr)),
+        });
+    }
+
     /// 触发初始化工厂
     /// 不返回值
-    pub fn do_init(&self){
+    pub fn do_init(&self) {
         self.core_addr.do_send(InitFactory);
     }
 
@@ -214,28 +1167,159 @@ impl BeanFactory {
         }
     }
 
-    pub async fn get_actor_by_name<T: Actor>(&self, name: &str) -> Option<Addr<T>> {
-        match self.core_addr.send(QueryBean(name.to_owned())).await {
-            Ok(v) => v
-                .map(|x| x.downcast::<Addr<T>>().ok())
-                .flatten()
-                .map(|x| x.as_ref().clone()),
-            Err(_) => None,
+    /// 被后注册的同名bean覆盖过的bean名称
+    pub async fn query_overridden_bean_names(&self) -> Vec<String> {
+        match self
+            .core_addr
+            .send(BeanFactoryCmd::QueryOverriddenBeanNames)
+            .await
+        {
+            Ok(resp) => resp.map_or(vec![], |r| match r {
+                BeanFactoryResult::BeanNames(v) => v,
+                _ => vec![],
+            }),
+            Err(_) => vec![],
         }
     }
 
+    /// 获取容器数据,获取原型及arbiter作用域的bean时在当前线程创建实例
+    async fn query_factory_data(&self) -> Option<FactoryData> {
+        match self.core_addr.send(BeanFactoryCmd::QueryFactoryData).await {
+            Ok(Some(BeanFactoryResult::FactoryData(v))) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// 按名称(qualifier)获取actor
+    pub async fn get_actor_by_name<T: Actor>(&self, name: &str) -> Option<Addr<T>> {
+        self.query_factory_data().await?.get_actor_by_name(name)
+    }
+
     pub async fn get_actor<T: Actor>(&self) -> Option<Addr<T>> {
-        self.get_actor_by_name(type_name::<T>()).await
+        self.query_factory_data().await?.get_actor()
     }
 
+    /// 按类型获取actor,选择规则同`FactoryData::try_get_actor`
+    pub async fn try_get_actor<T: Actor>(&self) -> Result<Addr<T>, BeanFactoryError> {
+        self.query_factory_data()
+            .await
+            .ok_or(BeanFactoryError::MailboxClosed)?
+            .try_get_actor()
+    }
+
+    /// 按名称(qualifier)获取bean
     pub async fn get_bean_by_name<T: 'static + Send + Sync>(&self, name: &str) -> Option<Arc<T>> {
-        match self.core_addr.send(QueryBean(name.to_owned())).await {
-            Ok(v) => v.map(|x| x.downcast::<T>().ok()).flatten(),
-            Err(_) => None,
-        }
+        self.query_factory_data().await?.get_bean_by_name(name)
     }
 
     pub async fn get_bean<T: 'static + Send + Sync>(&self) -> Option<Arc<T>> {
-        self.get_bean_by_name(type_name::<T>()).await
+        self.query_factory_data().await?.get_bean()
+    }
+
+    /// 按类型获取bean,选择规则同`FactoryData::try_get_bean`
+    pub async fn try_get_bean<T: 'static + Send + Sync>(&self) -> Result<Arc<T>, BeanFactoryError> {
+        self.query_factory_data()
+            .await
+            .ok_or(BeanFactoryError::MailboxClosed)?
+            .try_get_bean()
+    }
+
+    /// 按trait object类型获取bean,`T`一般为`dyn Trait`
+    pub async fn get_bean_dyn<T: ?Sized + 'static + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.query_factory_data().await?.get_bean_dyn()
+    }
+
+    /// 按trait object类型获取bean,找不到或有多个候选bean且没有主bean时返回错误
+    pub async fn try_get_bean_dyn<T: ?Sized + 'static + Send + Sync>(
+        &self,
+    ) -> Result<Arc<T>, BeanFactoryError> {
+        self.query_factory_data()
+            .await
+            .ok_or(BeanFactoryError::MailboxClosed)?
+            .try_get_bean_dyn()
+    }
+
+    /// 获取类型为`T`的所有actor
+    pub async fn get_actors<T: Actor>(&self) -> Vec<Addr<T>> {
+        self.query_factory_data()
+            .await
+            .map(|e| e.get_actors())
+            .unwrap_or_default()
+    }
+
+    /// 获取类型为`T`的所有bean
+    pub async fn get_beans<T: 'static + Send + Sync>(&self) -> Vec<Arc<T>> {
+        self.query_factory_data()
+            .await
+            .map(|e| e.get_beans())
+            .unwrap_or_default()
+    }
+
+    /// 获取提供trait object类型`T`的所有bean
+    pub async fn get_beans_of<T: ?Sized + 'static + Send + Sync>(&self) -> Vec<Arc<T>> {
+        self.query_factory_data()
+            .await
+            .map(|e| e.get_beans_of())
+            .unwrap_or_default()
+    }
+
+    /// 获取处理消息`M`的actor,不依赖actor的具体类型
+    pub async fn get_recipient<M>(&self) -> Option<Recipient<M>>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.query_factory_data().await?.get_recipient()
+    }
+
+    /// 获取处理消息`M`的actor,找不到或有多个候选actor且没有主bean时返回错误
+    pub async fn try_get_recipient<M>(&self) -> Result<Recipient<M>, BeanFactoryError>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.query_factory_data()
+            .await
+            .ok_or(BeanFactoryError::MailboxClosed)?
+            .try_get_recipient()
+    }
+
+    /// 按名称(qualifier)获取处理消息`M`的actor
+    pub async fn get_recipient_by_name<M>(&self, name: &str) -> Option<Recipient<M>>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.query_factory_data().await?.get_recipient_by_name(name)
+    }
+
+    /// 获取处理消息`M`的所有actor
+    pub async fn get_recipients<M>(&self) -> Vec<Recipient<M>>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.query_factory_data()
+            .await
+            .map(|e| e.get_recipients())
+            .unwrap_or_default()
+    }
+
+    /// 获取BeanFactoryCore最近发布的容器数据快照,不经过actor
+    /// 快照在初始化创建bean后及关闭工厂后更新
+    pub fn factory_data(&self) -> FactoryData {
+        self.snapshot.load().as_ref().clone()
+    }
+
+    /// 从快照中同步获取actor
+    /// 单例及延迟创建的单例(在工厂线程中创建)可以在非actix线程中获取;
+    /// 原型及arbiter作用域的actor在调用线程中启动,需要在actix线程中调用
+    pub fn get_actor_sync<T: Actor>(&self) -> Option<Addr<T>> {
+        self.snapshot.load().get_actor()
+    }
+
+    /// 从快照中同步获取bean,限制同`get_actor_sync`
+    pub fn get_bean_sync<T: 'static + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.snapshot.load().get_bean()
     }
 }
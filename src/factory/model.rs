@@ -1,162 +1,1412 @@
-use std::any::{type_name, Any};
-use std::{collections::HashMap, sync::Arc};
+use std::any::{type_name, Any, TypeId};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::thread::ThreadId;
+use std::time::Duration;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
 
 use actix::dev::ToEnvelope;
 use actix::prelude::*;
+use actix::WeakAddr;
+use arc_swap::ArcSwap;
+use config::{Config, ConfigError};
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+use serde::de::DeserializeOwned;
 
-use super::BeanFactory;
+use super::error::BeanFactoryError;
+use super::{BeanFactory, BeanFactoryCore};
 
 pub type DynAny = dyn Any + 'static + Send + Sync;
 
+/// bean创建失败时返回的错误
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// bean创建函数,参数为已创建的依赖bean
+pub type ProviderFn = Arc<dyn Fn(&FactoryData) -> Result<Arc<DynAny>, BoxError> + Send + Sync>;
+
+/// 异步创建bean的future,在BeanFactoryCore中等待完成
+pub type BeanFuture = Pin<Box<dyn Future<Output = Result<Arc<DynAny>, BoxError>>>>;
+
+/// 异步bean创建函数,参数为已创建的依赖bean
+pub type AsyncProviderFn = Arc<dyn Fn(FactoryData) -> BeanFuture + Send + Sync>;
+
+/// actor处理完事件的future,在BeanFactoryCore中等待完成
+pub type NotifyFuture = Pin<Box<dyn Future<Output = Result<(), MailboxError>>>>;
+
+/// actor bean的事件通知函数
+/// 第三个参数为true时返回等待actor处理完事件的future;为false时只发送事件,返回None
+pub type NotifyFn =
+    Arc<dyn Fn(Arc<DynAny>, FactoryEvent, bool) -> Option<NotifyFuture> + Send + Sync>;
+
+/// 把bean实例转换为`Arc<dyn Trait>`,转换结果再包装为`Arc<DynAny>`
+pub type CastFn = Arc<dyn Fn(Arc<DynAny>) -> Option<Arc<DynAny>> + Send + Sync>;
+
+/// 普通bean的销毁回调,关闭工厂时调用
+pub type DestroyFn = Arc<dyn Fn(Arc<DynAny>) + Send + Sync>;
+
+/// 关闭工厂时检查actor是否已停止的间隔
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// actor处理完销毁事件后等待其停止的时间,超过后不再等待
+const STOP_GRACE: Duration = Duration::from_millis(100);
+
+fn notify_actor<T>() -> NotifyFn
+where
+    T: Actor + Handler<FactoryEvent>,
+    <T as Actor>::Context: ToEnvelope<T, FactoryEvent>,
+{
+    Arc::new(|a, event, wait| {
+        let addr = a.downcast::<Addr<T>>().ok()?;
+        if !wait {
+            addr.do_send(event);
+            return None;
+        }
+        if let FactoryEvent::Destroy = &event {
+            //销毁事件需要等待actor停止;actor已停止时直接返回
+            //手写的事件处理可能不停止actor,处理完事件后最多等待`STOP_GRACE`
+            let request = addr.send(event);
+            return Some(Box::pin(async move {
+                if let Err(MailboxError::Timeout) = request.await {
+                    return Err(MailboxError::Timeout);
+                }
+                let deadline = actix::clock::Instant::now() + STOP_GRACE;
+                while addr.connected() {
+                    if actix::clock::Instant::now() >= deadline {
+                        log::warn!(
+                            "BeanFactory actor {} handled destroy event but did not stop",
+                            type_name::<T>()
+                        );
+                        break;
+                    }
+                    actix::clock::sleep(STOP_CHECK_INTERVAL).await;
+                }
+                Ok(())
+            }));
+        }
+        Some(Box::pin(addr.send(event)))
+    })
+}
+
+/// 受监督actor的事件通知函数
+/// 还有地址时`Supervisor`会重启停止的actor,销毁时发送事件后释放地址,处理完销毁事件后等待actor停止,
+/// 超过`STOP_GRACE`仍未停止时返回错误
+fn notify_supervised_actor<T>() -> NotifyFn
+where
+    T: Actor<Context = Context<T>> + Handler<FactoryEvent>,
+{
+    Arc::new(|a, event, wait| {
+        let addr = a.downcast::<Addr<T>>().ok()?;
+        if !wait {
+            addr.do_send(event);
+            return None;
+        }
+        if let FactoryEvent::Destroy = &event {
+            let weak = addr.downgrade();
+            let request = addr.send(event);
+            drop(addr);
+            return Some(Box::pin(async move {
+                if let Err(MailboxError::Timeout) = request.await {
+                    return Err(MailboxError::Timeout);
+                }
+                wait_stopped(weak).await
+            }));
+        }
+        Some(Box::pin(addr.send(event)))
+    })
+}
+
+/// 等待已释放地址的actor停止,超过`STOP_GRACE`仍未停止时返回`MailboxError::Timeout`
+/// 检查时会临时持有地址,第一次检查前先等待一个间隔,避免actor停止时因为临时地址被重启
+async fn wait_stopped<T: Actor>(addr: WeakAddr<T>) -> Result<(), MailboxError> {
+    let deadline = actix::clock::Instant::now() + STOP_GRACE;
+    loop {
+        actix::clock::sleep(STOP_CHECK_INTERVAL).await;
+        if addr.upgrade().is_none() {
+            return Ok(());
+        }
+        if actix::clock::Instant::now() >= deadline {
+            log::warn!(
+                "BeanFactory actor {} did not stop, its addresses are still held",
+                type_name::<T>()
+            );
+            return Err(MailboxError::Timeout);
+        }
+    }
+}
+
+/// SyncArbiter中actor的事件通知函数
+/// 事件记录到线程共享的`SyncEvents`中,`SyncFactoryEvent`只用于唤醒线程处理还没处理的事件;
+/// 等待时只等待还在运行的线程处理完事件
+fn notify_sync_actor<T>(pools: SyncPools<T>) -> NotifyFn
+where
+    T: Actor<Context = SyncContext<T>> + Handler<SyncFactoryEvent>,
+{
+    Arc::new(move |a, event, wait| {
+        let addr = a.downcast::<Addr<T>>().ok()?;
+        let events = find_sync_pool(&pools, &addr)?;
+        let seq = events.push(event.clone());
+        let weak = addr.downgrade();
+        drop(addr);
+        if !wait {
+            let fut = actix::clock::timeout(super::DEFAULT_INIT_TIMEOUT, events.deliver(weak, seq));
+            actix::spawn(fut);
+            return None;
+        }
+        if let FactoryEvent::Destroy = &event {
+            //所有地址都释放后线程才会退出
+            return Some(Box::pin(async move {
+                events.clone().deliver(weak, seq).await?;
+                events.wait_exited::<T>().await
+            }));
+        }
+        Some(Box::pin(events.deliver(weak, seq)))
+    })
+}
+
+/// `SyncArbiter`地址及其线程共享的事件记录,同一个bean定义创建的多个实例通过地址区分
+type SyncPools<T> = Arc<Mutex<Vec<(WeakAddr<T>, Arc<SyncEvents>)>>>;
+
+fn find_sync_pool<T: Actor>(pools: &SyncPools<T>, addr: &Addr<T>) -> Option<Arc<SyncEvents>> {
+    let mut pools = pools.lock().unwrap();
+    pools.retain(|(weak, _)| weak.upgrade().is_some());
+    pools
+        .iter()
+        .find(|(weak, _)| weak.upgrade().as_ref() == Some(addr))
+        .map(|(_, events)| events.clone())
+}
+
 #[derive(Clone)]
 pub enum Provieder {
-    Fn(Arc<dyn Fn() -> Option<Arc<DynAny>> + Send + Sync>),
+    Fn(ProviderFn),
+    /// 异步创建,只支持立即创建的单例bean
+    AsyncFn(AsyncProviderFn),
     Value(Arc<DynAny>),
 }
 
+/// bean作用域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BeanScope {
+    /// 整个容器只创建一个实例,在`BeanFactory::init`时创建
+    #[default]
+    Singleton,
+    /// 每次获取bean时都调用创建函数创建新实例
+    Prototype,
+    /// 每个actix Arbiter(线程)创建一个实例,在该线程第一次获取bean时创建
+    Arbiter,
+}
+
+/// bean在容器中的key
+/// 未指定名称的bean按类型区分,指定名称(qualifier)的bean按名称区分
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum BeanKey {
+    Type(TypeId),
+    Name(String),
+}
+
+/// bean对外提供的trait object类型
+#[derive(Clone)]
+pub struct BeanProvides {
+    /// `dyn Trait`的类型
+    pub type_id: TypeId,
+    pub type_name: String,
+    pub cast: CastFn,
+}
+
+/// bean的注册条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeanCondition {
+    /// 没有其它同类型(包括提供的trait object类型)的bean时才注册
+    OnMissingBean,
+    /// 设置了属性`key`时才注册;指定`value`时属性值需要相等
+    OnProperty { key: String, value: Option<String> },
+}
+
+/// bean依赖声明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeanDependency {
+    /// 依赖的bean名称或类型名称
+    pub name: String,
+    /// 按类型声明依赖时的类型,按名称声明依赖时为空
+    pub type_id: Option<TypeId>,
+    /// 必须存在的依赖,不存在时初始化失败;否则只影响初始化顺序
+    pub required: bool,
+    /// 只用于注入的依赖,尽量先初始化、后销毁,会形成循环依赖时忽略,互相注入的actor不会形成循环依赖
+    /// 依赖的bean被替换时仍会重新注入
+    pub inject: bool,
+    /// 注入该类型的所有bean,有多个候选bean时不需要选出唯一的bean
+    pub multiple: bool,
+}
+
+impl BeanDependency {
+    /// 注入类型为`T`的bean的依赖
+    pub fn inject_of<T: ?Sized + 'static>(required: bool) -> Self {
+        Self {
+            name: type_name::<T>().to_owned(),
+            type_id: Some(TypeId::of::<T>()),
+            required,
+            inject: true,
+            multiple: false,
+        }
+    }
+
+    /// 注入类型为`T`的所有bean的依赖
+    pub fn inject_all_of<T: ?Sized + 'static>(required: bool) -> Self {
+        Self {
+            multiple: true,
+            ..Self::inject_of::<T>(required)
+        }
+    }
+
+    /// 按名称(qualifier)注入bean的依赖
+    pub fn inject_by_name(name: &str, required: bool) -> Self {
+        Self {
+            name: name.to_owned(),
+            type_id: None,
+            required,
+            inject: true,
+            multiple: false,
+        }
+    }
+}
+
 #[derive(Clone, Message)]
 #[rtype(result = "()")]
 pub struct BeanDefinition {
+    /// 类型名称,只用于展示及按名称查找时兼容类型名称
     pub type_name: String,
+    /// bean类型,按类型获取bean时使用(actor类型直接使用actor类型)
+    pub type_id: TypeId,
+    /// bean名称(qualifier),为空时使用`type_name`
+    pub name: Option<String>,
     pub provider: Provieder,
-    pub notify: Option<Arc<dyn Fn(Arc<DynAny>, FactoryEvent) -> () + Send + Sync>>,
+    pub notify: Option<NotifyFn>,
+    /// 依赖的bean,初始化时依赖的bean会先于当前bean创建、注入
+    pub depends_on: Vec<BeanDependency>,
+    pub scope: BeanScope,
+    /// 延迟创建,单例bean在第一次获取时才创建并注入
+    pub lazy: bool,
+    /// 普通bean的销毁回调,关闭工厂时按初始化的逆序调用
+    pub destroy: Option<DestroyFn>,
+    /// bean可以作为这些trait object获取
+    pub provides: Vec<BeanProvides>,
+    /// 主bean,按类型获取时有多个候选bean,优先返回主bean
+    pub primary: bool,
+    /// 激活其中任一profile时才注册,为空时不限制
+    pub profiles: Vec<String>,
+    /// 注册条件,初始化前判断,不满足的bean不会注册
+    pub conditions: Vec<BeanCondition>,
     //pub inject: bool,
 }
 
 impl BeanDefinition {
     pub fn from_default<C: Default + Any + 'static + Send + Sync>() -> Self {
-        Self {
-            type_name: std::any::type_name::<C>().to_string(),
-            provider: Provieder::Fn(Arc::new(move || {
-                Some(C::default()).map(|x| Arc::new(x) as Arc<DynAny>)
-            })),
-            notify: None,
-        }
+        Self::base::<C>(
+            Provieder::Fn(Arc::new(move |_| Ok(Arc::new(C::default()) as Arc<DynAny>))),
+            None,
+        )
     }
 
     pub fn actor_from_default<T>() -> Self
     where
         T: Default + Actor<Context = Context<T>>,
     {
-        Self {
-            type_name: std::any::type_name::<T>().to_string(),
-            provider: Provieder::Fn(Arc::new(move || {
-                Some(T::default().start()).map(|x| Arc::new(x) as Arc<DynAny>)
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |_| {
+                Ok(Arc::new(T::default().start()) as Arc<DynAny>)
             })),
-            notify: None,
-        }
+            None,
+        )
     }
 
     pub fn actor_with_inject_from_default<T>() -> Self
     where
-        T: Default + Actor<Context = Context<T>> + Handler<FactoryEvent>,
+        T: Default + Actor<Context = Context<T>> + Handler<FactoryEvent> + Inject,
         <T as Actor>::Context: ToEnvelope<T, FactoryEvent>,
     {
-        Self {
-            type_name: std::any::type_name::<T>().to_string(),
-            provider: Provieder::Fn(Arc::new(move || {
-                Some(T::default().start()).map(|x| Arc::new(x) as Arc<DynAny>)
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |_| {
+                Ok(Arc::new(T::default().start()) as Arc<DynAny>)
             })),
-            notify: Some(Arc::new(|a, event| {
-                a.downcast::<Addr<T>>().ok().map(|e| e.do_send(event));
+            Some(notify_actor::<T>()),
+        )
+        .with_inject_dependencies::<T>()
+    }
+
+    /// 在`actix::Supervisor`下运行的actor,actor停止后会被重启,重启后地址不变
+    /// actor需要保存注入时收到的`BeanFactory`,在`Supervised::restarting`中调用`BeanFactory::notify_restarted`,
+    /// 重启后工厂重新注入该actor,并向依赖它的actor重新发送`FactoryEvent::Inject`及`FactoryEvent::Refreshed`
+    /// 还有地址时actor停止后总会被重启,关闭工厂前需要释放工厂外持有的地址
+    pub fn supervised_actor_from_default<T>() -> Self
+    where
+        T: Default + Actor<Context = Context<T>> + Supervised + Handler<FactoryEvent> + Inject,
+    {
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |_| {
+                Ok(Arc::new(Supervisor::start(|_| T::default())) as Arc<DynAny>)
             })),
-        }
+            Some(notify_supervised_actor::<T>()),
+        )
+        .with_inject_dependencies::<T>()
     }
 
-    pub fn from_fn<T: 'static + Send + Sync>(f: impl Fn() -> T + 'static + Send + Sync) -> Self {
-        Self {
-            type_name: std::any::type_name::<T>().to_string(),
-            provider: Provieder::Fn(Arc::new(move || {
-                Some(f()).map(|x| Arc::new(x) as Arc<DynAny>)
+    /// 在`SyncArbiter`中运行的actor,`threads`个线程各运行一个actor实例,共用同一个地址
+    /// 注入等工厂事件每个线程的actor都按顺序处理一次,actor需要实现`Handler<SyncFactoryEvent>`;
+    /// 线程之间不互相等待,已退出的线程不再处理事件
+    /// actor停止后`SyncArbiter`创建的新实例不会重新注入
+    /// 所有地址都释放后线程才会退出,关闭工厂前需要释放工厂外持有的地址及`FactoryData`,
+    /// 否则`BeanFactory::shutdown`返回`BeanFactoryError::DestroyTimeout`
+    pub fn sync_actor_from_default<T>(threads: usize) -> Self
+    where
+        T: Default + Actor<Context = SyncContext<T>> + Handler<SyncFactoryEvent> + Inject,
+    {
+        let pools: SyncPools<T> = Default::default();
+        let provider_pools = pools.clone();
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |_| {
+                let events = Arc::new(SyncEvents::new(threads));
+                let thread_events = events.clone();
+                let addr = SyncArbiter::start(threads, move || {
+                    thread_events.register();
+                    T::default()
+                });
+                let mut pools = provider_pools.lock().unwrap();
+                pools.push((addr.downgrade(), events));
+                Ok(Arc::new(addr) as Arc<DynAny>)
             })),
-            notify: None,
-        }
+            Some(notify_sync_actor::<T>(pools)),
+        )
+        .with_inject_dependencies::<T>()
+    }
+
+    pub fn from_fn<T: 'static + Send + Sync>(f: impl Fn() -> T + 'static + Send + Sync) -> Self {
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |_| Ok(Arc::new(f()) as Arc<DynAny>))),
+            None,
+        )
     }
 
     pub fn actor_from_fn<T: Actor>(f: impl Fn() -> Addr<T> + 'static + Send + Sync) -> Self {
-        Self {
-            type_name: std::any::type_name::<T>().to_string(),
-            provider: Provieder::Fn(Arc::new(move || {
-                Some(f()).map(|x| Arc::new(x) as Arc<DynAny>)
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |_| Ok(Arc::new(f()) as Arc<DynAny>))),
+            None,
+        )
+    }
+
+    /// 通过已创建的依赖bean构建bean,需要配合`depend_on`声明依赖
+    pub fn from_factory_fn<T: 'static + Send + Sync>(
+        f: impl Fn(&FactoryData) -> T + 'static + Send + Sync,
+    ) -> Self {
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |factory_data| {
+                Ok(Arc::new(f(factory_data)) as Arc<DynAny>)
             })),
-            notify: None,
-        }
+            None,
+        )
+    }
+
+    /// 通过已创建的依赖bean构建actor,需要配合`depend_on`声明依赖
+    pub fn actor_from_factory_fn<T: Actor>(
+        f: impl Fn(&FactoryData) -> Addr<T> + 'static + Send + Sync,
+    ) -> Self {
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |factory_data| {
+                Ok(Arc::new(f(factory_data)) as Arc<DynAny>)
+            })),
+            None,
+        )
+    }
+
+    /// 把前缀为`prefix`的属性(如`server.*`)绑定为配置结构体bean
+    /// 属性不存在或无法解析时,失败原因会在`BeanFactory::init`中返回
+    pub fn from_config<T>(prefix: &str) -> Self
+    where
+        T: DeserializeOwned + 'static + Send + Sync,
+    {
+        let prefix = prefix.to_owned();
+        Self::from_try_fn(move |factory_data| factory_data.get_property::<T>(&prefix))
+    }
+
+    /// 同`from_config`,绑定后调用`validate`校验配置,校验失败原因会在`BeanFactory::init`中返回
+    pub fn from_config_with<T, E>(
+        prefix: &str,
+        validate: impl Fn(&T) -> Result<(), E> + 'static + Send + Sync,
+    ) -> Self
+    where
+        T: DeserializeOwned + 'static + Send + Sync,
+        E: Into<BoxError>,
+    {
+        let prefix = prefix.to_owned();
+        Self::from_try_fn(move |factory_data| -> Result<T, BoxError> {
+            let config = factory_data.get_property::<T>(&prefix)?;
+            validate(&config).map_err(Into::into)?;
+            Ok(config)
+        })
+    }
+
+    /// 通过可能失败的函数构建bean,失败原因会在`BeanFactory::init`中返回
+    pub fn from_try_fn<T, E>(
+        f: impl Fn(&FactoryData) -> Result<T, E> + 'static + Send + Sync,
+    ) -> Self
+    where
+        T: 'static + Send + Sync,
+        E: Into<BoxError>,
+    {
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |factory_data| {
+                f(factory_data)
+                    .map(|x| Arc::new(x) as Arc<DynAny>)
+                    .map_err(Into::into)
+            })),
+            None,
+        )
+    }
+
+    /// 通过可能失败的函数构建actor,失败原因会在`BeanFactory::init`中返回
+    pub fn actor_from_try_fn<T, E>(
+        f: impl Fn(&FactoryData) -> Result<Addr<T>, E> + 'static + Send + Sync,
+    ) -> Self
+    where
+        T: Actor,
+        E: Into<BoxError>,
+    {
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |factory_data| {
+                f(factory_data)
+                    .map(|x| Arc::new(x) as Arc<DynAny>)
+                    .map_err(Into::into)
+            })),
+            None,
+        )
     }
 
     pub fn actor_with_inject_from_fn<T>(f: impl Fn() -> Addr<T> + 'static + Send + Sync) -> Self
     where
-        T: Actor<Context = Context<T>> + Handler<FactoryEvent>,
+        T: Actor<Context = Context<T>> + Handler<FactoryEvent> + Inject,
         <T as Actor>::Context: ToEnvelope<T, FactoryEvent>,
     {
-        Self {
-            type_name: std::any::type_name::<T>().to_string(),
-            provider: Provieder::Fn(Arc::new(move || {
-                Some(f()).map(|x| Arc::new(x) as Arc<DynAny>)
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |_| Ok(Arc::new(f()) as Arc<DynAny>))),
+            Some(notify_actor::<T>()),
+        )
+        .with_inject_dependencies::<T>()
+    }
+
+    /// 通过可能失败的函数构建需要注入的actor
+    pub fn actor_with_inject_from_try_fn<T, E>(
+        f: impl Fn(&FactoryData) -> Result<Addr<T>, E> + 'static + Send + Sync,
+    ) -> Self
+    where
+        T: Actor<Context = Context<T>> + Handler<FactoryEvent> + Inject,
+        <T as Actor>::Context: ToEnvelope<T, FactoryEvent>,
+        E: Into<BoxError>,
+    {
+        Self::base::<T>(
+            Provieder::Fn(Arc::new(move |factory_data| {
+                f(factory_data)
+                    .map(|x| Arc::new(x) as Arc<DynAny>)
+                    .map_err(Into::into)
             })),
-            notify: Some(Arc::new(|a, event| {
-                a.downcast::<Addr<T>>().ok().map(|e| e.do_send(event));
+            Some(notify_actor::<T>()),
+        )
+        .with_inject_dependencies::<T>()
+    }
+
+    /// 通过异步函数构建bean,在`BeanFactory::init`中按依赖顺序等待创建完成
+    pub fn from_async_fn<T, E, F, Fut>(f: F) -> Self
+    where
+        T: 'static + Send + Sync,
+        E: Into<BoxError>,
+        F: Fn(FactoryData) -> Fut + 'static + Send + Sync,
+        Fut: Future<Output = Result<T, E>> + 'static,
+    {
+        Self::base::<T>(
+            Provieder::AsyncFn(Arc::new(move |factory_data| -> BeanFuture {
+                let fut = f(factory_data);
+                Box::pin(async move {
+                    fut.await
+                        .map(|x| Arc::new(x) as Arc<DynAny>)
+                        .map_err(Into::into)
+                })
             })),
-        }
+            None,
+        )
     }
 
-    pub fn from_obj<T: 'static + Send + Sync>(v: Arc<T>) -> Self {
+    /// 通过异步函数构建actor,在`BeanFactory::init`中按依赖顺序等待创建完成
+    pub fn actor_from_async_fn<T, E, F, Fut>(f: F) -> Self
+    where
+        T: Actor,
+        E: Into<BoxError>,
+        F: Fn(FactoryData) -> Fut + 'static + Send + Sync,
+        Fut: Future<Output = Result<Addr<T>, E>> + 'static,
+    {
+        Self::base::<T>(
+            Provieder::AsyncFn(Arc::new(move |factory_data| -> BeanFuture {
+                let fut = f(factory_data);
+                Box::pin(async move {
+                    fut.await
+                        .map(|x| Arc::new(x) as Arc<DynAny>)
+                        .map_err(Into::into)
+                })
+            })),
+            None,
+        )
+    }
+
+    /// 通过异步函数构建需要注入的actor
+    pub fn actor_with_inject_from_async_fn<T, E, F, Fut>(f: F) -> Self
+    where
+        T: Actor<Context = Context<T>> + Handler<FactoryEvent> + Inject,
+        <T as Actor>::Context: ToEnvelope<T, FactoryEvent>,
+        E: Into<BoxError>,
+        F: Fn(FactoryData) -> Fut + 'static + Send + Sync,
+        Fut: Future<Output = Result<Addr<T>, E>> + 'static,
+    {
+        let mut bean = Self::actor_from_async_fn(f);
+        bean.notify = Some(notify_actor::<T>());
+        bean.with_inject_dependencies::<T>()
+    }
+
+    fn base<T: ?Sized + 'static>(provider: Provieder, notify: Option<NotifyFn>) -> Self {
         Self {
             type_name: std::any::type_name::<T>().to_string(),
-            provider: Provieder::Value(v),
-            notify: None,
+            type_id: TypeId::of::<T>(),
+            name: None,
+            provider,
+            notify,
+            depends_on: vec![],
+            scope: BeanScope::Singleton,
+            lazy: false,
+            destroy: None,
+            provides: vec![],
+            primary: false,
+            profiles: vec![],
+            conditions: vec![],
         }
     }
 
+    pub fn from_obj<T: 'static + Send + Sync>(v: Arc<T>) -> Self {
+        Self::base::<T>(Provieder::Value(v), None)
+    }
+
     pub fn actor_from_obj<T: Actor>(v: Addr<T>) -> Self {
-        Self {
-            type_name: std::any::type_name::<T>().to_string(),
-            provider: Provieder::Value(Arc::new(v)),
-            notify: None,
-        }
+        Self::base::<T>(Provieder::Value(Arc::new(v)), None)
     }
 
     pub fn actor_with_inject_from_obj<T>(v: Addr<T>) -> Self
     where
-        T: Actor<Context = Context<T>> + Handler<FactoryEvent>,
+        T: Actor<Context = Context<T>> + Handler<FactoryEvent> + Inject,
         <T as Actor>::Context: ToEnvelope<T, FactoryEvent>,
     {
-        Self {
-            type_name: std::any::type_name::<T>().to_string(),
-            provider: Provieder::Value(Arc::new(v)),
-            notify: Some(Arc::new(|a, event| {
-                a.downcast::<Addr<T>>().ok().map(|e| e.do_send(event));
-            })),
+        Self::base::<T>(Provieder::Value(Arc::new(v)), Some(notify_actor::<T>()))
+            .with_inject_dependencies::<T>()
+    }
+
+    /// bean名称,未指定名称时为类型名称
+    pub fn bean_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.type_name)
+    }
+
+    pub(crate) fn key(&self) -> BeanKey {
+        match &self.name {
+            Some(name) => BeanKey::Name(name.to_owned()),
+            None => BeanKey::Type(self.type_id),
         }
     }
+
+    /// 指定bean名称(qualifier),同一类型可以按不同名称注册多个bean
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    /// 指定bean作用域,默认为`BeanScope::Singleton`
+    pub fn with_scope(mut self, scope: BeanScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// 延迟创建,单例bean在第一次通过`BeanFactory`、`FactoryData`或`QueryBean`获取时才创建并注入
+    /// 并发获取时只会创建一个实例;实例在工厂所在的线程中创建,不要在工厂线程中阻塞等待其它线程获取
+    pub fn lazy(mut self) -> Self {
+        self.lazy = true;
+        self
+    }
+
+    /// 标记为主bean,按类型(包括trait object及消息类型)获取时有多个候选bean,优先返回主bean
+    pub fn primary(mut self) -> Self {
+        self.primary = true;
+        self
+    }
+
+    /// 激活profile`profile`时才注册,可以声明多个profile
+    pub fn profile(mut self, profile: &str) -> Self {
+        self.profiles.push(profile.to_owned());
+        self
+    }
+
+    /// 没有其它同类型的bean时才注册,用于提供默认实现
+    pub fn on_missing_bean(mut self) -> Self {
+        self.conditions.push(BeanCondition::OnMissingBean);
+        self
+    }
+
+    /// 设置了属性`key`时才注册
+    pub fn on_property(mut self, key: &str) -> Self {
+        self.conditions.push(BeanCondition::OnProperty {
+            key: key.to_owned(),
+            value: None,
+        });
+        self
+    }
+
+    /// 属性`key`的值为`value`时才注册
+    pub fn on_property_value(mut self, key: &str, value: &str) -> Self {
+        self.conditions.push(BeanCondition::OnProperty {
+            key: key.to_owned(),
+            value: Some(value.to_owned()),
+        });
+        self
+    }
+
+    /// 是否需要在初始化前判断注册条件
+    pub(crate) fn is_conditional(&self) -> bool {
+        !self.profiles.is_empty() || !self.conditions.is_empty()
+    }
+
+    /// 设置销毁回调,`BeanFactory::shutdown`时按初始化的逆序调用
+    /// 只对已创建的单例bean(包括已创建的延迟bean)生效
+    pub fn on_destroy<T: 'static + Send + Sync>(
+        mut self,
+        f: impl Fn(&T) + 'static + Send + Sync,
+    ) -> Self {
+        self.destroy = Some(Arc::new(move |a| {
+            if let Ok(v) = a.downcast::<T>() {
+                f(&v);
+            }
+        }));
+        self
+    }
+
+    /// 声明bean可以作为`Arc<D>`获取,`D`一般为`dyn Trait`
+    /// 例如:`.provides(|v: Arc<MemoryStore>| v as Arc<dyn ConfigStore>)`
+    pub fn provides<T, D>(mut self, cast: fn(Arc<T>) -> Arc<D>) -> Self
+    where
+        T: 'static + Send + Sync,
+        D: ?Sized + 'static + Send + Sync,
+    {
+        let type_id = TypeId::of::<D>();
+        if self.provides.iter().any(|e| e.type_id == type_id) {
+            return self;
+        }
+        self.provides.push(BeanProvides {
+            type_id,
+            type_name: type_name::<D>().to_owned(),
+            cast: Arc::new(move |a| {
+                let v = a.downcast::<T>().ok()?;
+                Some(Arc::new(cast(v)) as Arc<DynAny>)
+            }),
+        });
+        self
+    }
+
+    /// 声明actor bean处理消息`M`,可以通过`FactoryData::get_recipient::<M>`或`get_recipients::<M>`获取
+    /// 依赖方可以通过`depend_on::<Recipient<M>>`声明依赖,不需要依赖actor的具体类型
+    /// `T`必须是该bean的actor类型,否则panic
+    pub fn handles<T, M>(mut self) -> Self
+    where
+        T: Actor + Handler<M>,
+        T::Context: ToEnvelope<T, M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        assert!(
+            TypeId::of::<T>() == self.type_id,
+            "bean {} can not handle messages as {}",
+            self.type_name,
+            type_name::<T>()
+        );
+        let type_id = TypeId::of::<Recipient<M>>();
+        if self.provides.iter().any(|e| e.type_id == type_id) {
+            return self;
+        }
+        self.provides.push(BeanProvides {
+            type_id,
+            type_name: type_name::<Recipient<M>>().to_owned(),
+            cast: Arc::new(|a| {
+                let addr = a.downcast::<Addr<T>>().ok()?;
+                Some(Arc::new(addr.as_ref().clone().recipient()) as Arc<DynAny>)
+            }),
+        });
+        self
+    }
+
+    /// 是否可以按类型`type_id`获取,包括提供的trait object类型
+    pub(crate) fn is_type_of(&self, type_id: TypeId) -> bool {
+        self.type_id == type_id || self.provides.iter().any(|e| e.type_id == type_id)
+    }
+
+    /// 声明依赖类型为`T`的bean(actor类型直接使用actor类型)
+    /// 该类型有多个命名bean时,依赖其中所有bean
+    pub fn depend_on<T: ?Sized + 'static>(self) -> Self {
+        self.add_dependency(BeanDependency {
+            inject: false,
+            ..BeanDependency::inject_of::<T>(false)
+        })
+    }
+
+    /// 按名称(qualifier)声明依赖的bean
+    pub fn depend_on_name(self, name: &str) -> Self {
+        self.add_dependency(BeanDependency {
+            inject: false,
+            ..BeanDependency::inject_by_name(name, false)
+        })
+    }
+
+    /// 声明必须存在的依赖,依赖不存在或创建失败时`BeanFactory::init`返回错误
+    pub fn require<T: ?Sized + 'static>(self) -> Self {
+        self.add_dependency(BeanDependency {
+            inject: false,
+            ..BeanDependency::inject_of::<T>(true)
+        })
+    }
+
+    /// 按名称(qualifier)声明必须存在的依赖
+    pub fn require_name(self, name: &str) -> Self {
+        self.add_dependency(BeanDependency {
+            inject: false,
+            ..BeanDependency::inject_by_name(name, true)
+        })
+    }
+
+    /// 声明依赖,同一依赖多次声明时合并:任一声明为必须依赖即为必须依赖,
+    /// 任一声明影响初始化顺序即影响初始化顺序
+    pub fn add_dependency(mut self, dependency: BeanDependency) -> Self {
+        match self
+            .depends_on
+            .iter_mut()
+            .find(|e| e.name == dependency.name && e.type_id == dependency.type_id)
+        {
+            Some(dep) => {
+                dep.required |= dependency.required;
+                dep.inject &= dependency.inject;
+                dep.multiple &= dependency.multiple;
+            }
+            None => self.depends_on.push(dependency),
+        }
+        self
+    }
+
+    /// 声明`T`注入字段的依赖
+    fn with_inject_dependencies<T: Inject>(self) -> Self {
+        T::dependencies()
+            .into_iter()
+            .fold(self, |bean, dep| bean.add_dependency(dep))
+    }
 }
 
 inventory::collect!(BeanDefinition);
 
-#[derive(Debug, Clone)]
-pub struct FactoryData(pub Arc<HashMap<String, Arc<DynAny>>>);
+/// 非单例bean的创建信息
+#[derive(Clone)]
+pub(crate) struct ScopedBean {
+    name: String,
+    provider: ProviderFn,
+    notify: Option<NotifyFn>,
+}
+
+impl ScopedBean {
+    pub(crate) fn new(name: &str, provider: ProviderFn, notify: Option<NotifyFn>) -> Self {
+        Self {
+            name: name.to_owned(),
+            provider,
+            notify,
+        }
+    }
+
+    /// 创建新实例,需要注入的actor创建后立即触发注入
+    fn create(&self, factory_data: &FactoryData) -> Option<Arc<DynAny>> {
+        match self.try_create(factory_data) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                log::error!("BeanFactory create bean {} error: {}", &self.name, e);
+                None
+            }
+        }
+    }
+
+    fn try_create(&self, factory_data: &FactoryData) -> Result<Arc<DynAny>, BoxError> {
+        let v = (self.provider)(factory_data)?;
+        if let (Some(notify), Some(factory)) = (self.notify.as_ref(), factory_data.factory()) {
+            let inject_event = FactoryEvent::Inject {
+                factory,
+                factory_data: factory_data.clone(),
+            };
+            notify(v.clone(), inject_event, false);
+            notify(v.clone(), FactoryEvent::Complete, false);
+        }
+        Ok(v)
+    }
+}
+
+/// 容器中的bean实例
+#[derive(Clone)]
+pub(crate) enum BeanInstance {
+    Singleton(Arc<DynAny>),
+    Prototype(ScopedBean),
+    Arbiter(ScopedBean, Arc<DashMap<ThreadId, Arc<DynAny>>>),
+    Lazy(ScopedBean, Arc<OnceCell<Arc<DynAny>>>),
+}
+
+impl BeanInstance {
+    fn resolve(&self, factory_data: &FactoryData) -> Option<Arc<DynAny>> {
+        match self {
+            BeanInstance::Singleton(v) => Some(v.clone()),
+            BeanInstance::Prototype(bean) => bean.create(factory_data),
+            BeanInstance::Arbiter(bean, instances) => {
+                let thread_id = std::thread::current().id();
+                if let Some(v) = instances.get(&thread_id) {
+                    return Some(v.value().clone());
+                }
+                //同一线程不会并发创建,创建时不持有锁,避免创建函数中获取其它bean时死锁
+                let v = bean.create(factory_data)?;
+                instances.insert(thread_id, v.clone());
+                Some(v)
+            }
+            BeanInstance::Lazy(bean, cell) => {
+                if let Some(v) = cell.get() {
+                    return Some(v.clone());
+                }
+                //在工厂线程中创建,actor不会在调用线程中启动;创建失败时不保存结果,下次获取时重新创建
+                let (bean, cell) = (bean.clone(), cell.clone());
+                factory_data
+                    .run_in_factory(move |factory_data| {
+                        match cell.get_or_try_init(|| bean.try_create(factory_data)) {
+                            Ok(v) => Some(v.clone()),
+                            Err(e) => {
+                                log::error!(
+                                    "BeanFactory create lazy bean {} error: {}",
+                                    &bean.name,
+                                    e
+                                );
+                                None
+                            }
+                        }
+                    })
+                    .flatten()
+            }
+        }
+    }
+}
+
+/// 容器中的bean
+#[derive(Clone)]
+pub(crate) struct BeanEntry {
+    /// bean名称,未指定名称时为类型名称
+    name: String,
+    type_id: TypeId,
+    provides: Vec<BeanProvides>,
+    primary: bool,
+    instance: BeanInstance,
+}
+
+/// 工厂地址、工厂发布的快照及工厂所在的线程
+#[derive(Clone)]
+struct FactoryHandle {
+    addr: WeakAddr<BeanFactoryCore>,
+    snapshot: Weak<ArcSwap<FactoryData>>,
+    arbiter: ArbiterHandle,
+    thread_id: ThreadId,
+}
+
+/// 容器数据,保存已创建的bean
+/// 未指定名称的bean按类型(`TypeId`)保存,指定名称(qualifier)的bean按名称保存,同一类型可以注册多个
+/// 按类型获取bean时使用`TypeId`,类型名称只用于展示及按名称获取时兼容
+/// 获取bean时按bean的作用域返回实例
+#[derive(Clone, Default)]
+pub struct FactoryData {
+    beans: Arc<HashMap<BeanKey, BeanEntry>>,
+    /// 类型 -> 该类型的bean列表(按创建顺序)
+    type_beans: Arc<HashMap<TypeId, Vec<BeanKey>>>,
+    /// 类型名称 -> 类型,按名称获取未指定名称的bean时使用
+    type_names: Arc<HashMap<String, Vec<TypeId>>>,
+    /// 工厂地址、工厂发布的快照及工厂所在的线程,用于延迟及非单例actor创建后触发注入
+    factory: Option<FactoryHandle>,
+    /// 父工厂发布的快照,当前容器找不到bean时到父工厂查找
+    parent: Option<Arc<ArcSwap<FactoryData>>>,
+    /// 初始化时加载的属性
+    properties: Option<Arc<Config>>,
+}
+
+impl fmt::Debug for FactoryData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FactoryData")
+            .field("beans", &self.bean_names())
+            .finish()
+    }
+}
 
 impl FactoryData {
+    /// 在工厂actor中调用,记录工厂地址及所在线程
+    pub(crate) fn set_factory(
+        &mut self,
+        factory: WeakAddr<BeanFactoryCore>,
+        snapshot: &Arc<ArcSwap<FactoryData>>,
+    ) {
+        self.factory = Some(FactoryHandle {
+            addr: factory,
+            snapshot: Arc::downgrade(snapshot),
+            arbiter: Arbiter::current(),
+            thread_id: std::thread::current().id(),
+        });
+    }
+
+    /// 在工厂所在的线程中执行`f`并等待结果,当前线程就是工厂线程时直接执行
+    /// 工厂已停止时返回None
+    fn run_in_factory<R, F>(&self, f: F) -> Option<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&FactoryData) -> R + Send + 'static,
+    {
+        let handle = match self.factory.as_ref() {
+            Some(handle) if handle.thread_id != std::thread::current().id() => handle,
+            _ => return Some(f(self)),
+        };
+        let factory_data = self.clone();
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        let spawned = handle.arbiter.spawn_fn(move || {
+            tx.send(f(&factory_data)).ok();
+        });
+        if !spawned {
+            log::error!("BeanFactory has stopped");
+            return None;
+        }
+        rx.recv().ok()
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: Arc<ArcSwap<FactoryData>>) {
+        self.parent = Some(parent);
+    }
+
+    /// 清空bean,保留工厂及父工厂信息
+    pub(crate) fn cleared(&self) -> FactoryData {
+        FactoryData {
+            factory: self.factory.clone(),
+            parent: self.parent.clone(),
+            properties: self.properties.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn set_properties(&mut self, properties: Config) {
+        self.properties = Some(Arc::new(properties));
+    }
+
+    /// 父工厂当前的容器数据
+    fn parent(&self) -> Option<Arc<FactoryData>> {
+        self.parent.as_ref().map(|e| e.load_full())
+    }
+
+    /// 父工厂(包括更上层的工厂)中是否存在满足依赖的bean
+    pub(crate) fn parent_contains(&self, dep: &BeanDependency) -> bool {
+        let parent = match self.parent() {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let found = match dep.type_id {
+            Some(type_id) => parent.type_beans.contains_key(&type_id),
+            None => {
+                parent
+                    .beans
+                    .contains_key(&BeanKey::Name(dep.name.to_owned()))
+                    || parent.type_names.contains_key(&dep.name)
+            }
+        };
+        found || parent.parent_contains(dep)
+    }
+
+    fn factory(&self) -> Option<BeanFactory> {
+        let handle = self.factory.as_ref()?;
+        let core_addr = handle.addr.upgrade()?;
+        match handle.snapshot.upgrade() {
+            Some(snapshot) => Some(BeanFactory::with_snapshot(core_addr, snapshot)),
+            None => Some(BeanFactory::new_by_core(core_addr)),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, bean: &BeanDefinition, instance: BeanInstance) {
+        let key = bean.key();
+        let entry = BeanEntry {
+            name: bean.bean_name().to_owned(),
+            type_id: bean.type_id,
+            provides: bean.provides.clone(),
+            primary: bean.primary,
+            instance,
+        };
+        Arc::make_mut(&mut self.beans).insert(key.clone(), entry);
+        let type_ids = std::iter::once(bean.type_id).chain(bean.provides.iter().map(|e| e.type_id));
+        for type_id in type_ids {
+            let keys = Arc::make_mut(&mut self.type_beans)
+                .entry(type_id)
+                .or_default();
+            if !keys.contains(&key) {
+                keys.push(key.clone());
+            }
+        }
+        let type_ids = Arc::make_mut(&mut self.type_names)
+            .entry(bean.type_name.to_owned())
+            .or_default();
+        if !type_ids.contains(&bean.type_id) {
+            type_ids.push(bean.type_id);
+        }
+    }
+
+    pub(crate) fn contains(&self, key: &BeanKey) -> bool {
+        self.beans.contains_key(key)
+    }
+
+    /// 已创建的单例bean,不会触发创建
+    pub(crate) fn get_singleton(&self, key: &BeanKey) -> Option<Arc<DynAny>> {
+        match self.beans.get(key).map(|e| &e.instance) {
+            Some(BeanInstance::Singleton(v)) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    /// 已创建的单例bean,包括已创建的延迟bean
+    pub(crate) fn get_created(&self, key: &BeanKey) -> Option<Arc<DynAny>> {
+        match self.beans.get(key).map(|e| &e.instance) {
+            Some(BeanInstance::Singleton(v)) => Some(v.clone()),
+            Some(BeanInstance::Lazy(_, cell)) => cell.get().cloned(),
+            _ => None,
+        }
+    }
+
+    fn resolve(&self, key: &BeanKey) -> Option<Arc<DynAny>> {
+        self.beans.get(key).and_then(|e| e.instance.resolve(self))
+    }
+
+    /// 按类型`type_id`获取bean实例;`type_id`为bean提供的trait object类型时,返回转换后的`Arc<dyn Trait>`
+    fn resolve_as(&self, key: &BeanKey, type_id: TypeId) -> Option<Arc<DynAny>> {
+        let entry = self.beans.get(key)?;
+        let v = entry.instance.resolve(self)?;
+        if entry.type_id == type_id {
+            return Some(v);
+        }
+        let provides = entry.provides.iter().find(|e| e.type_id == type_id)?;
+        (provides.cast)(v)
+    }
+
+    /// 按类型查找bean的key
+    /// 有多个候选bean时选择唯一的主bean(primary),没有唯一的主bean时返回`AmbiguousBean`
+    fn find_by_type(&self, type_id: TypeId, type_name: &str) -> Result<&BeanKey, BeanFactoryError> {
+        let keys = match self.type_beans.get(&type_id).map(|e| e.as_slice()) {
+            None | Some([]) => return Err(BeanFactoryError::BeanNotFound(type_name.to_owned())),
+            Some([key]) => return Ok(key),
+            Some(keys) => keys,
+        };
+        let primary: Vec<&BeanKey> = keys
+            .iter()
+            .filter(|e| self.beans.get(e).map(|e| e.primary).unwrap_or(false))
+            .collect();
+        let candidates: Vec<&BeanKey> = match primary.as_slice() {
+            [key] => return Ok(key),
+            [] => keys.iter().collect(),
+            _ => primary,
+        };
+        Err(BeanFactoryError::AmbiguousBean {
+            type_name: type_name.to_owned(),
+            candidates: candidates
+                .into_iter()
+                .filter_map(|e| self.beans.get(e))
+                .map(|e| e.name.to_owned())
+                .collect(),
+        })
+    }
+
+    /// 按类型查找bean,`type_id`为trait object类型时,返回转换后的`Arc<dyn Trait>`
+    /// 有多个候选bean且无法选出唯一的bean时返回`AmbiguousBean`
+    /// 当前容器没有该类型的bean时,到父工厂查找
+    pub(crate) fn get_by_type(
+        &self,
+        type_id: TypeId,
+        type_name: &str,
+    ) -> Result<Arc<DynAny>, BeanFactoryError> {
+        let key = match self.find_by_type(type_id, type_name) {
+            Ok(key) => key,
+            Err(BeanFactoryError::BeanNotFound(_)) if self.parent.is_some() => {
+                return self.parent().unwrap().get_by_type(type_id, type_name);
+            }
+            Err(e) => return Err(e),
+        };
+        self.resolve_as(key, type_id)
+            .ok_or_else(|| BeanFactoryError::BeanNotFound(type_name.to_owned()))
+    }
+
+    /// 检查按类型能否选出唯一的bean,规则同`get_by_type`,不创建实例
+    pub(crate) fn check_by_type(
+        &self,
+        type_id: TypeId,
+        type_name: &str,
+    ) -> Result<(), BeanFactoryError> {
+        match self.find_by_type(type_id, type_name) {
+            Ok(_) => Ok(()),
+            Err(BeanFactoryError::BeanNotFound(_)) if self.parent.is_some() => {
+                self.parent().unwrap().check_by_type(type_id, type_name)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 检查按名称能否找到bean,规则同`get`,不创建实例
+    pub(crate) fn check_by_name(&self, name: &str) -> Result<(), BeanFactoryError> {
+        if self.beans.contains_key(&BeanKey::Name(name.to_owned())) {
+            return Ok(());
+        }
+        match (
+            self.type_names.get(name).map(|e| e.as_slice()),
+            self.parent(),
+        ) {
+            (Some([type_id]), _) if self.beans.contains_key(&BeanKey::Type(*type_id)) => Ok(()),
+            (Some([type_id]), _) => self.check_by_type(*type_id, name),
+            (_, Some(parent)) => parent.check_by_name(name),
+            _ => Err(BeanFactoryError::BeanNotFound(name.to_owned())),
+        }
+    }
+
+    /// 按名称查找bean;没有该名称的bean时,把名称当作类型名称,该类型名称只对应一个类型时
+    /// 返回该类型未指定名称的bean,没有时按类型查找;当前容器找不到时到父工厂查找
+    pub(crate) fn get(&self, name: &str) -> Option<Arc<DynAny>> {
+        let key = BeanKey::Name(name.to_owned());
+        if self.beans.contains_key(&key) {
+            return self.resolve(&key);
+        }
+        match self.type_names.get(name).map(|e| e.as_slice()) {
+            Some([type_id]) if self.beans.contains_key(&BeanKey::Type(*type_id)) => {
+                self.resolve(&BeanKey::Type(*type_id))
+            }
+            Some([type_id]) => self.get_by_type(*type_id, name).ok(),
+            _ => self.parent().and_then(|e| e.get(name)),
+        }
+    }
+
+    /// 按名称查找bean,并转换为类型`type_id`;名称的处理同`get`
+    pub(crate) fn get_as(&self, name: &str, type_id: TypeId) -> Option<Arc<DynAny>> {
+        let key = BeanKey::Name(name.to_owned());
+        if self.beans.contains_key(&key) {
+            return self.resolve_as(&key, type_id);
+        }
+        match self.type_names.get(name).map(|e| e.as_slice()) {
+            Some([bean_type]) => self.resolve_as(&BeanKey::Type(*bean_type), type_id),
+            _ => self.parent().and_then(|e| e.get_as(name, type_id)),
+        }
+    }
+
+    /// 类型为`type_id`的所有bean,按创建顺序排列,父工厂的bean排在后面
+    pub(crate) fn get_all(&self, type_id: TypeId) -> Vec<Arc<DynAny>> {
+        let mut beans: Vec<Arc<DynAny>> = self
+            .type_beans
+            .get(&type_id)
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|e| self.resolve_as(e, type_id))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(parent) = self.parent() {
+            beans.extend(parent.get_all(type_id));
+        }
+        beans
+    }
+
+    /// 所有bean名称,不包括父工厂的bean
+    pub fn bean_names(&self) -> Vec<String> {
+        self.beans.values().map(|e| e.name.to_owned()).collect()
+    }
+
+    /// 类型为`T`的所有bean名称(actor类型直接使用actor类型),不包括父工厂的bean
+    pub fn bean_names_of<T: ?Sized + 'static>(&self) -> Vec<String> {
+        self.type_beans
+            .get(&TypeId::of::<T>())
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|e| self.beans.get(e))
+                    .map(|e| e.name.to_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 按名称(qualifier)查找actor
     pub fn get_actor_by_name<T: Actor>(&self, name: &str) -> Option<Addr<T>> {
-        self.0
-            .get(name)
-            .map(|x| x.clone().downcast::<Addr<T>>().ok())
-            .flatten()
+        self.get(name)
+            .and_then(|x| x.downcast::<Addr<T>>().ok())
             .map(|x| x.as_ref().clone())
     }
 
+    /// 按类型查找actor,选择规则同`try_get_actor`,无法选出唯一的actor时返回`None`
     pub fn get_actor<T: Actor>(&self) -> Option<Addr<T>> {
-        self.get_actor_by_name(type_name::<T>())
+        self.try_get_actor().ok()
     }
 
+    /// 按类型查找actor,有多个候选actor时选择唯一的主bean(primary),
+    /// 没有唯一的主bean时返回`AmbiguousBean`
+    pub fn try_get_actor<T: Actor>(&self) -> Result<Addr<T>, BeanFactoryError> {
+        let type_name = type_name::<T>();
+        self.get_by_type(TypeId::of::<T>(), type_name)?
+            .downcast::<Addr<T>>()
+            .map(|x| x.as_ref().clone())
+            .map_err(|_| BeanFactoryError::BeanNotFound(type_name.to_owned()))
+    }
+
+    /// 类型为`T`的所有actor
+    pub fn get_actors<T: Actor>(&self) -> Vec<Addr<T>> {
+        self.get_all(TypeId::of::<T>())
+            .into_iter()
+            .filter_map(|x| x.downcast::<Addr<T>>().ok())
+            .map(|x| x.as_ref().clone())
+            .collect()
+    }
+
+    /// 按名称(qualifier)查找bean
     pub fn get_bean_by_name<T: 'static + Send + Sync>(&self, name: &str) -> Option<Arc<T>> {
-        self.0
-            .get(name)
-            .map(|x| x.clone().downcast::<T>().ok())
-            .flatten()
+        self.get(name).and_then(|x| x.downcast::<T>().ok())
     }
 
+    /// 按类型查找bean,选择规则同`try_get_bean`,无法选出唯一的bean时返回`None`
     pub fn get_bean<T: 'static + Send + Sync>(&self) -> Option<Arc<T>> {
-        self.get_bean_by_name(type_name::<T>())
+        self.try_get_bean().ok()
+    }
+
+    /// 按类型查找bean,有多个候选bean时选择唯一的主bean(primary),
+    /// 没有唯一的主bean时返回`AmbiguousBean`
+    pub fn try_get_bean<T: 'static + Send + Sync>(&self) -> Result<Arc<T>, BeanFactoryError> {
+        let type_name = type_name::<T>();
+        self.get_by_type(TypeId::of::<T>(), type_name)?
+            .downcast::<T>()
+            .map_err(|_| BeanFactoryError::BeanNotFound(type_name.to_owned()))
+    }
+
+    /// 按trait object类型获取bean,`T`一般为`dyn Trait`
+    /// bean需要通过`BeanDefinition::provides`声明提供该类型;有多个bean提供该类型时返回其中的主bean
+    pub fn get_bean_dyn<T: ?Sized + 'static + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.try_get_bean_dyn().ok()
+    }
+
+    /// 按trait object类型获取bean,找不到或有多个候选bean且没有主bean时返回错误
+    pub fn try_get_bean_dyn<T: ?Sized + 'static + Send + Sync>(
+        &self,
+    ) -> Result<Arc<T>, BeanFactoryError> {
+        let type_name = type_name::<T>();
+        self.get_by_type(TypeId::of::<T>(), type_name)?
+            .downcast::<Arc<T>>()
+            .map(|x| x.as_ref().clone())
+            .map_err(|_| BeanFactoryError::BeanNotFound(type_name.to_owned()))
+    }
+
+    /// 类型为`T`的所有bean
+    pub fn get_beans<T: 'static + Send + Sync>(&self) -> Vec<Arc<T>> {
+        self.get_all(TypeId::of::<T>())
+            .into_iter()
+            .filter_map(|x| x.downcast::<T>().ok())
+            .collect()
+    }
+
+    /// 提供trait object类型`T`的所有bean,按初始化顺序排列
+    pub fn get_beans_of<T: ?Sized + 'static + Send + Sync>(&self) -> Vec<Arc<T>> {
+        self.get_all(TypeId::of::<T>())
+            .into_iter()
+            .filter_map(|x| x.downcast::<Arc<T>>().ok())
+            .map(|x| x.as_ref().clone())
+            .collect()
+    }
+
+    /// 通过`BeanDefinition::handles`声明处理消息`M`的actor;有多个actor处理该消息时返回其中的主bean
+    pub fn get_recipient<M>(&self) -> Option<Recipient<M>>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.try_get_recipient().ok()
+    }
+
+    /// 查找处理消息`M`的actor,找不到或有多个候选actor且没有主bean时返回错误
+    pub fn try_get_recipient<M>(&self) -> Result<Recipient<M>, BeanFactoryError>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let type_name = type_name::<Recipient<M>>();
+        self.get_by_type(TypeId::of::<Recipient<M>>(), type_name)?
+            .downcast::<Recipient<M>>()
+            .map(|x| x.as_ref().clone())
+            .map_err(|_| BeanFactoryError::BeanNotFound(type_name.to_owned()))
+    }
+
+    /// 按名称(qualifier)查找处理消息`M`的actor
+    pub fn get_recipient_by_name<M>(&self, name: &str) -> Option<Recipient<M>>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.get_as(name, TypeId::of::<Recipient<M>>())
+            .and_then(|x| x.downcast::<Recipient<M>>().ok())
+            .map(|x| x.as_ref().clone())
+    }
+
+    /// 通过`BeanDefinition::handles`声明处理消息`M`的所有actor,按初始化顺序排列
+    pub fn get_recipients<M>(&self) -> Vec<Recipient<M>>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.get_all(TypeId::of::<Recipient<M>>())
+            .into_iter()
+            .filter_map(|x| x.downcast::<Recipient<M>>().ok())
+            .map(|x| x.as_ref().clone())
+            .collect()
+    }
+
+    /// 获取属性并解析为类型`T`,当前工厂没有该属性时到父工厂查找
+    /// 属性名按`.`分隔层级,如`db.url`;前缀(如`db`)可以解析为结构体
+    pub fn get_property<T: DeserializeOwned>(&self, key: &str) -> Result<T, BeanFactoryError> {
+        let result = match &self.properties {
+            Some(properties) => properties.get::<T>(key),
+            None => Err(ConfigError::NotFound(key.to_owned())),
+        };
+        match result {
+            Ok(v) => Ok(v),
+            Err(ConfigError::NotFound(_)) => match self.parent() {
+                Some(parent) => parent.get_property(key),
+                None => Err(BeanFactoryError::PropertyNotFound(key.to_owned())),
+            },
+            Err(e) => Err(BeanFactoryError::InvalidProperty {
+                key: key.to_owned(),
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    /// 获取属性,属性不存在时返回None;属性值无法解析时输出错误日志并返回None
+    pub fn get_property_opt<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        match self.get_property(key) {
+            Ok(v) => Some(v),
+            Err(BeanFactoryError::PropertyNotFound(_)) => None,
+            Err(e) => {
+                log::error!("BeanFactory get property error: {}", e);
+                None
+            }
+        }
     }
 }
 
@@ -164,37 +1414,280 @@ impl FactoryData {
 pub trait Inject {
     type Context;
     fn inject(&mut self, factory_data: FactoryData, factory: BeanFactory, ctx: &mut Self::Context);
-    fn complete(&mut self, ctx: &mut Self::Context) {} 
+    fn complete(&mut self, ctx: &mut Self::Context) {}
+    /// 注入字段依赖的bean,`BeanDefinition::actor_with_inject_*`等构造函数会自动声明这些依赖
+    /// 用于检查必须依赖、替换bean后重新注入及排序,不会形成循环依赖
+    fn dependencies() -> Vec<BeanDependency>
+    where
+        Self: Sized,
+    {
+        vec![]
+    }
+    /// 关闭工厂时调用,调用后actor会停止
+    fn destroy(&mut self, ctx: &mut Self::Context) {}
+    /// 依赖的bean`bean`被替换,重新注入后调用
+    fn refreshed(&mut self, bean: String, ctx: &mut Self::Context) {}
 }
 
 #[derive(Message)]
-#[rtype(result = "Option<FactoryData>")]
+#[rtype(result = "Result<FactoryData, BeanFactoryError>")]
 pub struct InitFactory;
 
+#[derive(Message)]
+#[rtype(result = "Result<(), BeanFactoryError>")]
+pub struct ShutdownFactory;
+
+/// `SyncArbiter`的线程共享的工厂事件记录
+/// 每个线程按顺序处理自己还没处理的事件,所有线程都处理过的事件会被移除
+struct SyncEvents {
+    state: Mutex<SyncEventsState>,
+    /// 已发送还没处理的`SyncFactoryEvent`数量
+    queued: AtomicUsize,
+}
+
+struct SyncEventsState {
+    /// 线程数量
+    size: usize,
+    /// 已启动的线程数量,包括已退出的线程
+    started: usize,
+    /// 运行中的线程 -> 已处理的事件数量
+    threads: HashMap<ThreadId, usize>,
+    /// 第一个未移除事件的序号
+    first: usize,
+    events: VecDeque<FactoryEvent>,
+}
+
+impl SyncEvents {
+    fn new(size: usize) -> Self {
+        Self {
+            state: Mutex::new(SyncEventsState {
+                size,
+                started: 0,
+                threads: HashMap::new(),
+                first: 0,
+                events: VecDeque::new(),
+            }),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// 在线程中创建actor时调用,记录运行中的线程,线程退出时移除
+    fn register(self: &Arc<Self>) {
+        let thread_id = std::thread::current().id();
+        let mut state = self.state.lock().unwrap();
+        if state.threads.contains_key(&thread_id) {
+            return;
+        }
+        state.started += 1;
+        //还没移除的事件都没有被所有线程处理过,新启动的线程从第一个事件开始处理
+        let first = state.first;
+        state.threads.insert(thread_id, first);
+        SYNC_THREAD_GUARDS.with(|guards| {
+            guards.borrow_mut().push(SyncThreadGuard {
+                events: Arc::downgrade(self),
+                thread_id,
+            })
+        });
+    }
+
+    /// 记录事件,返回处理完该事件后已处理的事件数量
+    fn push(&self, event: FactoryEvent) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.events.push_back(event);
+        state.first + state.events.len()
+    }
+
+    /// 当前线程下一个需要处理的事件
+    fn next(&self, thread_id: ThreadId) -> Option<FactoryEvent> {
+        let state = self.state.lock().unwrap();
+        let handled = *state.threads.get(&thread_id)?;
+        state.events.get(handled - state.first).cloned()
+    }
+
+    /// 当前线程处理完一个事件,所有线程都已启动时移除都处理过的事件
+    fn handled(&self, thread_id: ThreadId) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(handled) = state.threads.get_mut(&thread_id) {
+            *handled += 1;
+        }
+        if state.started < state.size {
+            return;
+        }
+        let min = state.threads.values().min().copied().unwrap_or(usize::MAX);
+        while state.first < min && state.events.pop_front().is_some() {
+            state.first += 1;
+        }
+    }
+
+    /// 还没处理完前`seq`个事件的线程数量,包括还没启动的线程
+    fn lagging(&self, seq: usize) -> usize {
+        let state = self.state.lock().unwrap();
+        let pending = state.threads.values().filter(|handled| **handled < seq);
+        state.size - state.started + pending.count()
+    }
+
+    /// 唤醒线程处理事件,等待运行中的线程都处理完前`seq`个事件
+    /// 空闲的线程可能收到其它线程的唤醒事件,没有处理完时定时补发
+    async fn deliver<T>(self: Arc<Self>, addr: WeakAddr<T>, seq: usize) -> Result<(), MailboxError>
+    where
+        T: Actor<Context = SyncContext<T>> + Handler<SyncFactoryEvent>,
+    {
+        loop {
+            let lagging = self.lagging(seq);
+            if lagging == 0 {
+                return Ok(());
+            }
+            let addr = addr.upgrade().ok_or(MailboxError::Closed)?;
+            while self.queued.load(Ordering::Acquire) < lagging {
+                self.queued.fetch_add(1, Ordering::AcqRel);
+                addr.do_send(SyncFactoryEvent {
+                    events: self.clone(),
+                });
+            }
+            drop(addr);
+            actix::clock::sleep(STOP_CHECK_INTERVAL).await;
+        }
+    }
+
+    /// 等待所有线程退出,超过`STOP_GRACE`仍有线程运行时返回`MailboxError::Timeout`
+    async fn wait_exited<T>(&self) -> Result<(), MailboxError> {
+        let deadline = actix::clock::Instant::now() + STOP_GRACE;
+        while !self.state.lock().unwrap().threads.is_empty() {
+            if actix::clock::Instant::now() >= deadline {
+                log::warn!(
+                    "BeanFactory sync actor {} did not stop, its addresses are still held",
+                    type_name::<T>()
+                );
+                return Err(MailboxError::Timeout);
+            }
+            actix::clock::sleep(STOP_CHECK_INTERVAL).await;
+        }
+        Ok(())
+    }
+}
+
+/// 线程退出(包括处理事件时panic)时从事件记录中移除该线程
+struct SyncThreadGuard {
+    events: Weak<SyncEvents>,
+    thread_id: ThreadId,
+}
+
+impl Drop for SyncThreadGuard {
+    fn drop(&mut self) {
+        if let Some(events) = self.events.upgrade() {
+            if let Ok(mut state) = events.state.lock() {
+                state.threads.remove(&self.thread_id);
+            }
+        }
+    }
+}
+
+thread_local! {
+    static SYNC_THREAD_GUARDS: RefCell<Vec<SyncThreadGuard>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 唤醒`SyncArbiter`中的线程处理工厂事件
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SyncFactoryEvent {
+    events: Arc<SyncEvents>,
+}
+
+impl SyncFactoryEvent {
+    /// 按顺序把当前线程还没处理的事件交给`f`处理,在`Handler<SyncFactoryEvent>`中调用
+    pub fn handle_with(self, mut f: impl FnMut(FactoryEvent)) {
+        self.events.queued.fetch_sub(1, Ordering::AcqRel);
+        let thread_id = std::thread::current().id();
+        while let Some(event) = self.events.next(thread_id) {
+            f(event);
+            self.events.handled(thread_id);
+        }
+    }
+}
+
+/// 受监督的actor已重启,`matches`判断bean实例是否为该actor
+#[derive(Message)]
+#[rtype(result = "()")]
+pub(crate) struct ActorRestarted {
+    pub type_id: TypeId,
+    pub matches: Box<dyn Fn(&DynAny) -> bool + Send>,
+}
+
+/// 运行时替换bean
+#[derive(Message)]
+#[rtype(result = "Result<FactoryData, BeanFactoryError>")]
+pub struct ReplaceBean(pub BeanDefinition);
+
+/// 属性来源,按添加顺序加载,后加载的覆盖先加载的;`BeanFactory::set_property`设置的属性优先级最高
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertySource {
+    /// 配置文件,按扩展名解析为TOML或JSON
+    File(PathBuf),
+    /// 前缀为`prefix`的环境变量,层级之间用`__`分隔,如前缀`APP`时`APP_DB__URL`对应属性`db.url`
+    Env(String),
+}
+
+/// 同名bean重复注册时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// 拒绝后注册的bean,`BeanFactory::init`返回`DuplicateRegistration`错误
+    Reject,
+    /// 后注册的bean覆盖先注册的bean,并输出警告日志
+    #[default]
+    Override,
+    /// 保留先注册的bean,忽略后注册的bean
+    KeepFirst,
+}
+
 #[derive(Message)]
 #[rtype(result = "Option<BeanFactoryResult>")]
 pub enum BeanFactoryCmd {
     Init,
     QueryBean(String),
     QueryBeanNames,
+    QueryFactoryData,
+    QueryOverriddenBeanNames,
+    SetDuplicatePolicy(DuplicatePolicy),
+    /// 设置初始化时等待actor处理注入事件的超时时间
+    SetInitTimeout(Duration),
+    /// 设置激活的profile
+    SetActiveProfiles(Vec<String>),
+    /// 添加属性来源
+    AddPropertySource(PropertySource),
+    /// 设置属性,覆盖属性来源中的同名属性
+    SetProperty(String, String),
 }
 
 pub enum BeanFactoryResult {
     None,
     BeanNames(Vec<String>),
     Bean(Option<Arc<DynAny>>),
+    FactoryData(FactoryData),
 }
 
 #[derive(Message)]
 #[rtype(result = "Option<Arc<DynAny>>")]
 pub struct QueryBean(pub String);
 
+/// 通过`BeanFactory::new_by_core`创建的工厂,接收BeanFactoryCore发布的快照
+#[derive(Message)]
+#[rtype(result = "()")]
+pub(crate) struct AttachSnapshot(pub Weak<ArcSwap<FactoryData>>);
+
+/// 工厂发送给actor的事件,后续版本可能增加新的事件,匹配时需要保留`_`分支
 #[derive(Message, Clone)]
 #[rtype(result = "()")]
+#[non_exhaustive]
 pub enum FactoryEvent {
     Inject {
         factory: BeanFactory,
         factory_data: FactoryData,
     },
     Complete,
+    /// 关闭工厂,actor处理后应该停止;处理后没有停止的actor在`STOP_GRACE`后视为已销毁
+    Destroy,
+    /// 依赖的bean`bean`被替换,在重新注入后发送
+    Refreshed {
+        bean: String,
+    },
 }
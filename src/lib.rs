@@ -1,8 +1,13 @@
 pub mod factory;
 
-pub use actix::prelude::{Addr, Handler};
+pub use actix::prelude::{Actor, ActorContext, Addr, AsyncContext, Handler, Recipient, Supervised};
 pub use factory::{
-    model::{BeanDefinition, FactoryData, FactoryEvent, Inject},
+    error::BeanFactoryError,
+    model::{
+        BeanCondition, BeanDefinition, BeanDependency, BeanProvides, BeanScope, BoxError, CastFn,
+        DestroyFn, DuplicatePolicy, FactoryData, FactoryEvent, Inject, PropertySource,
+        SyncFactoryEvent,
+    },
     BeanFactory, BeanFactoryCore,
 };
 
@@ -1,10 +1,20 @@
 //use std::{any::type_name, sync::Arc};
+use std::{
+    any::type_name,
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
 use actix::prelude::*;
 
 use bean_factory::{
-    setup_submitted_beans, ActorComponent, BeanDefinition, BeanFactory, BeanFactoryCore,
-    FactoryData, Inject, InjectComponent,
+    bean, register_beans, setup_submitted_beans, ActorComponent, BeanDefinition, BeanFactory,
+    BeanFactoryCore, BeanFactoryError, BeanScope, BoxError, DuplicatePolicy, FactoryData,
+    FactoryEvent, Inject, InjectComponent,
 };
 
 struct Ping(usize);
@@ -62,6 +72,7 @@ impl Actor for MyActor {
 impl Handler<Ping> for MyActor {
     type Result = usize;
 
+    #[allow(clippy::option_map_unit_fn)]
     fn handle(&mut self, msg: Ping, _: &mut Context<Self>) -> Self::Result {
         self.count += msg.0;
         self.foo_addr.as_ref().map(|x| x.do_send(Ping(self.count)));
@@ -141,6 +152,1639 @@ async fn register_002() {
     take(&factory).await;
 }
 
+struct DbPool {
+    url: String,
+}
+
+struct AccountDao {
+    pool: Option<Arc<DbPool>>,
+}
+
+#[actix::test]
+async fn depend_on_001() {
+    let factory = BeanFactory::new();
+    factory.register(
+        BeanDefinition::from_factory_fn(|factory_data| AccountDao {
+            pool: factory_data.get_bean::<DbPool>(),
+        })
+        .depend_on::<DbPool>(),
+    );
+    factory.register(BeanDefinition::from_fn(|| DbPool {
+        url: "mysql://localhost".to_owned(),
+    }));
+    let factory_data = factory.init().await.unwrap();
+    let dao: Arc<AccountDao> = factory_data.get_bean().unwrap();
+    assert_eq!(dao.pool.as_ref().unwrap().url, "mysql://localhost");
+}
+
+struct CycleA;
+struct CycleB;
+
+#[actix::test]
+async fn depend_on_cycle_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::from_fn(|| CycleA).depend_on::<CycleB>());
+    factory.register(BeanDefinition::from_fn(|| CycleB).depend_on::<CycleA>());
+    let err = factory.init().await.unwrap_err();
+    assert_eq!(
+        err,
+        BeanFactoryError::CircularDependency(vec![
+            type_name::<CycleA>().to_owned(),
+            type_name::<CycleB>().to_owned(),
+            type_name::<CycleA>().to_owned(),
+        ])
+    );
+    assert!(factory.get_bean::<CycleA>().await.is_none());
+}
+
+struct BrokenPool;
+struct BrokenDao;
+
+#[actix::test]
+async fn provider_error_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::from_try_fn(|_| {
+        Err::<BrokenPool, _>("connect refused")
+    }));
+    factory.register(BeanDefinition::from_fn(|| BrokenDao).require::<BrokenPool>());
+    factory.register(BeanDefinition::from_fn(|| CycleA));
+    let err = factory.init().await.unwrap_err();
+    assert_eq!(
+        err,
+        BeanFactoryError::Multiple(vec![
+            BeanFactoryError::ProviderFailed {
+                bean: type_name::<BrokenPool>().to_owned(),
+                message: "connect refused".to_owned(),
+            },
+            BeanFactoryError::MissingDependency {
+                bean: type_name::<BrokenDao>().to_owned(),
+                dependency: type_name::<BrokenPool>().to_owned(),
+            },
+        ])
+    );
+    assert!(factory.get_bean::<CycleA>().await.is_some());
+}
+
+struct ConnectionPool {
+    url: String,
+}
+
+fn pool_bean(url: &'static str) -> BeanDefinition {
+    BeanDefinition::from_fn(move || ConnectionPool {
+        url: url.to_owned(),
+    })
+}
+
+#[bean(register, name = "named_component")]
+#[derive(Default)]
+struct NamedComponent {}
+
+#[actix::test]
+async fn named_bean_001() {
+    let factory = BeanFactory::new();
+    factory.register(
+        BeanDefinition::from_fn(|| ConnectionPool {
+            url: "primary".to_owned(),
+        })
+        .with_name("primary"),
+    );
+    factory.register(
+        BeanDefinition::from_fn(|| ConnectionPool {
+            url: "replica".to_owned(),
+        })
+        .with_name("replica"),
+    );
+    register_beans(&factory);
+    let factory_data = factory.init().await.unwrap();
+    let pool: Arc<ConnectionPool> = factory_data.get_bean_by_name("replica").unwrap();
+    assert_eq!(pool.url, "replica");
+    assert!(factory_data.get_bean::<ConnectionPool>().is_none());
+    let mut urls: Vec<String> = factory
+        .get_beans::<ConnectionPool>()
+        .await
+        .iter()
+        .map(|e| e.url.clone())
+        .collect();
+    urls.sort();
+    assert_eq!(urls, vec!["primary", "replica"]);
+    assert!(factory
+        .get_bean_by_name::<NamedComponent>("named_component")
+        .await
+        .is_some());
+}
+
+#[actix::test]
+async fn duplicate_policy_001() {
+    let factory = BeanFactory::new();
+    factory.register(pool_bean("first"));
+    factory.register(pool_bean("second"));
+    let factory_data = factory.init().await.unwrap();
+    assert_eq!(
+        factory_data.get_bean::<ConnectionPool>().unwrap().url,
+        "second"
+    );
+    assert_eq!(
+        factory.query_overridden_bean_names().await,
+        vec![type_name::<ConnectionPool>()]
+    );
+
+    let factory = BeanFactory::new();
+    factory.set_duplicate_policy(DuplicatePolicy::KeepFirst);
+    factory.register(pool_bean("first"));
+    factory.register(pool_bean("second"));
+    let factory_data = factory.init().await.unwrap();
+    assert_eq!(
+        factory_data.get_bean::<ConnectionPool>().unwrap().url,
+        "first"
+    );
+    assert!(factory.query_overridden_bean_names().await.is_empty());
+
+    let factory = BeanFactory::new();
+    factory.set_duplicate_policy(DuplicatePolicy::Reject);
+    factory.register(pool_bean("first"));
+    factory.register(pool_bean("second"));
+    assert_eq!(
+        factory.init().await.unwrap_err(),
+        BeanFactoryError::DuplicateRegistration(type_name::<ConnectionPool>().to_owned())
+    );
+}
+
+struct QueryInjected;
+
+impl Message for QueryInjected {
+    type Result = (bool, Option<String>, bool);
+}
+
+#[bean(inject, complete = "on_complete")]
+#[derive(Default)]
+struct DeriveInjectActor {
+    #[inject(required)]
+    foo_addr: Option<Addr<FooActor>>,
+    #[inject(name = "replica")]
+    pool: Option<Arc<ConnectionPool>>,
+    completed: bool,
+}
+
+impl DeriveInjectActor {
+    fn on_complete(&mut self, _ctx: &mut Context<Self>) {
+        self.completed = true;
+    }
+}
+
+impl Actor for DeriveInjectActor {
+    type Context = Context<Self>;
+}
+
+impl Handler<QueryInjected> for DeriveInjectActor {
+    type Result = MessageResult<QueryInjected>;
+
+    fn handle(&mut self, _msg: QueryInjected, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult((
+            self.foo_addr.is_some(),
+            self.pool.as_ref().map(|e| e.url.clone()),
+            self.completed,
+        ))
+    }
+}
+
+#[actix::test]
+async fn derive_inject_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::actor_with_inject_from_default::<
+        DeriveInjectActor,
+    >());
+    factory.register(BeanDefinition::actor_from_default::<FooActor>());
+    factory.register(
+        BeanDefinition::from_fn(|| ConnectionPool {
+            url: "replica".to_owned(),
+        })
+        .with_name("replica"),
+    );
+    let factory_data = factory.init().await.unwrap();
+    let addr: Addr<DeriveInjectActor> = factory_data.get_actor().unwrap();
+    let (foo_injected, pool_url, completed) = addr.send(QueryInjected).await.unwrap();
+    assert!(foo_injected);
+    assert_eq!(pool_url.as_deref(), Some("replica"));
+    assert!(completed);
+}
+
+#[actix::test]
+async fn derive_inject_required_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::actor_with_inject_from_default::<
+        DeriveInjectActor,
+    >());
+    assert_eq!(
+        factory.init().await.unwrap_err(),
+        BeanFactoryError::MissingDependency {
+            bean: type_name::<DeriveInjectActor>().to_owned(),
+            dependency: type_name::<FooActor>().to_owned(),
+        }
+    );
+
+    //按类型注入有多个候选bean且没有主bean时无法注入
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::actor_with_inject_from_default::<
+        DeriveInjectActor,
+    >());
+    factory.register(BeanDefinition::actor_from_default::<FooActor>().with_name("a"));
+    factory.register(BeanDefinition::actor_from_default::<FooActor>().with_name("b"));
+    match factory.init().await {
+        Err(BeanFactoryError::AmbiguousBean {
+            type_name: name,
+            mut candidates,
+        }) => {
+            assert_eq!(name, type_name::<FooActor>());
+            candidates.sort();
+            assert_eq!(candidates, vec!["a", "b"]);
+        }
+        _ => panic!("expected ambiguous bean error"),
+    }
+}
+
+#[bean(inject)]
+#[derive(Default)]
+struct MutualPing {
+    #[inject(required)]
+    pong: Option<Addr<MutualPong>>,
+}
+
+impl Actor for MutualPing {
+    type Context = Context<Self>;
+}
+
+#[bean(inject)]
+#[derive(Default)]
+struct MutualPong {
+    #[inject(required)]
+    ping: Option<Addr<MutualPing>>,
+}
+
+impl Actor for MutualPong {
+    type Context = Context<Self>;
+}
+
+struct QueryPeer;
+
+impl Message for QueryPeer {
+    type Result = bool;
+}
+
+impl Handler<QueryPeer> for MutualPing {
+    type Result = bool;
+
+    fn handle(&mut self, _msg: QueryPeer, _ctx: &mut Self::Context) -> Self::Result {
+        self.pong.is_some()
+    }
+}
+
+impl Handler<QueryPeer> for MutualPong {
+    type Result = bool;
+
+    fn handle(&mut self, _msg: QueryPeer, _ctx: &mut Self::Context) -> Self::Result {
+        self.ping.is_some()
+    }
+}
+
+#[actix::test]
+async fn mutual_inject_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::actor_with_inject_from_default::<MutualPing>());
+    factory.register(BeanDefinition::actor_with_inject_from_default::<MutualPong>());
+    let factory_data = factory.init().await.unwrap();
+    let ping: Addr<MutualPing> = factory_data.get_actor().unwrap();
+    let pong: Addr<MutualPong> = factory_data.get_actor().unwrap();
+    assert!(ping.send(QueryPeer).await.unwrap());
+    assert!(pong.send(QueryPeer).await.unwrap());
+}
+
+struct ReportService;
+struct MailClient;
+
+#[actix::test]
+async fn required_dependency_001() {
+    let factory = BeanFactory::new();
+    factory.register(
+        BeanDefinition::from_fn(|| ReportService)
+            .require::<MailClient>()
+            .require_name("archive_store"),
+    );
+    factory.register(BeanDefinition::from_fn(|| CycleA).depend_on::<MailClient>());
+    let err = factory.init().await.unwrap_err();
+    assert_eq!(
+        err,
+        BeanFactoryError::Multiple(vec![
+            BeanFactoryError::MissingDependency {
+                bean: type_name::<ReportService>().to_owned(),
+                dependency: type_name::<MailClient>().to_owned(),
+            },
+            BeanFactoryError::MissingDependency {
+                bean: type_name::<ReportService>().to_owned(),
+                dependency: "archive_store".to_owned(),
+            },
+        ])
+    );
+    assert!(factory.get_bean::<CycleA>().await.is_none());
+}
+
+struct RequestContext {
+    id: usize,
+}
+
+#[actix::test]
+async fn bean_scope_001() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let prototype_counter = counter.clone();
+    let factory = BeanFactory::new();
+    factory.register(
+        BeanDefinition::from_fn(move || RequestContext {
+            id: prototype_counter.fetch_add(1, Ordering::SeqCst),
+        })
+        .with_scope(BeanScope::Prototype),
+    );
+    factory.register(
+        BeanDefinition::from_fn(|| ConnectionPool {
+            url: format!("{:?}", std::thread::current().id()),
+        })
+        .with_scope(BeanScope::Arbiter),
+    );
+    let factory_data = factory.init().await.unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 0);
+    let first: Arc<RequestContext> = factory_data.get_bean().unwrap();
+    let second: Arc<RequestContext> = factory.get_bean().await.unwrap();
+    assert_eq!((first.id, second.id), (0, 1));
+
+    let pool: Arc<ConnectionPool> = factory.get_bean().await.unwrap();
+    let same_pool: Arc<ConnectionPool> = factory_data.get_bean().unwrap();
+    assert!(Arc::ptr_eq(&pool, &same_pool));
+
+    let arbiter = Arbiter::new();
+    let (tx, rx) = std::sync::mpsc::channel();
+    arbiter.spawn(async move {
+        tx.send(factory_data.get_bean::<ConnectionPool>().unwrap())
+            .unwrap();
+    });
+    let other_pool = rx.recv().unwrap();
+    assert!(!Arc::ptr_eq(&pool, &other_pool));
+    assert_ne!(pool.url, other_pool.url);
+    arbiter.stop();
+}
+
+struct HeavyCache;
+
+#[actix::test]
+async fn lazy_bean_001() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let lazy_counter = counter.clone();
+    //延迟创建的bean在工厂线程中创建,工厂运行在独立线程中,等待其它线程获取时不会阻塞工厂
+    let factory = BeanFactory::spawn_new();
+    factory.register(
+        BeanDefinition::from_fn(move || {
+            lazy_counter.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            HeavyCache
+        })
+        .lazy(),
+    );
+    factory.register(BeanDefinition::actor_with_inject_from_default::<DeriveInjectActor>().lazy());
+    factory.register(BeanDefinition::actor_from_default::<FooActor>());
+    let factory_data = factory.init().await.unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 0);
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let factory_data = factory_data.clone();
+            std::thread::spawn(move || factory_data.get_bean::<HeavyCache>().unwrap())
+        })
+        .collect();
+    let caches: Vec<Arc<HeavyCache>> = handles.into_iter().map(|e| e.join().unwrap()).collect();
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+    assert!(caches.iter().all(|e| Arc::ptr_eq(e, &caches[0])));
+
+    let addr: Addr<DeriveInjectActor> = factory.get_actor().await.unwrap();
+    let (foo_injected, _, completed) = addr.send(QueryInjected).await.unwrap();
+    assert!(foo_injected && completed);
+    let same_addr: Addr<DeriveInjectActor> = factory_data.get_actor().unwrap();
+    assert_eq!(addr, same_addr);
+}
+
+#[bean(inject)]
+#[derive(Default)]
+struct LazyWorker {
+    #[inject]
+    foo_addr: Option<Addr<FooActor>>,
+}
+
+impl Actor for LazyWorker {
+    type Context = Context<Self>;
+}
+
+impl Handler<QueryPeer> for LazyWorker {
+    type Result = bool;
+
+    fn handle(&mut self, _msg: QueryPeer, _ctx: &mut Self::Context) -> Self::Result {
+        self.foo_addr.is_some()
+    }
+}
+
+#[actix::test]
+async fn lazy_actor_sync_001() {
+    let factory = BeanFactory::spawn_new();
+    factory.register(BeanDefinition::actor_with_inject_from_default::<LazyWorker>().lazy());
+    factory.register(BeanDefinition::actor_from_default::<FooActor>());
+    factory.init().await.unwrap();
+
+    //非actix线程中获取延迟创建的actor,actor在工厂线程中启动
+    let sync_factory = factory.clone();
+    let worker = std::thread::spawn(move || sync_factory.get_actor_sync::<LazyWorker>())
+        .join()
+        .unwrap()
+        .unwrap();
+    let mut injected = false;
+    for _ in 0..100 {
+        injected = worker.send(QueryPeer).await.unwrap();
+        if injected {
+            break;
+        }
+        actix::clock::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(injected);
+    assert_eq!(factory.get_actor::<LazyWorker>().await.unwrap(), worker);
+    factory.shutdown().await.unwrap();
+}
+
+struct DaoHolder {
+    dao: Option<Arc<AccountDao>>,
+}
+
+#[actix::test]
+async fn async_provider_001() {
+    let factory = BeanFactory::new();
+    factory.register(
+        BeanDefinition::from_async_fn(|factory_data: FactoryData| async move {
+            actix::clock::sleep(Duration::from_millis(50)).await;
+            let pool = factory_data
+                .get_bean::<DbPool>()
+                .ok_or("db pool is not ready")?;
+            Ok::<_, BoxError>(AccountDao { pool: Some(pool) })
+        })
+        .require::<DbPool>(),
+    );
+    factory.register(BeanDefinition::from_fn(|| DbPool {
+        url: "mysql://localhost".to_owned(),
+    }));
+    factory.register(
+        BeanDefinition::from_factory_fn(|factory_data| DaoHolder {
+            dao: factory_data.get_bean(),
+        })
+        .require::<AccountDao>(),
+    );
+    let init_factory = factory.clone();
+    let init_handle = actix::spawn(async move { init_factory.init().await });
+    actix::clock::sleep(Duration::from_millis(10)).await;
+    assert_eq!(factory.query_bean_names().await.len(), 3);
+    assert!(!init_handle.is_finished());
+
+    let factory_data = init_handle.await.unwrap().unwrap();
+    let holder: Arc<DaoHolder> = factory_data.get_bean().unwrap();
+    let pool = holder.dao.as_ref().unwrap().pool.as_ref().unwrap();
+    assert_eq!(pool.url, "mysql://localhost");
+}
+
+fn slow_dao_factory() -> BeanFactory {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::from_async_fn(|_| async move {
+        actix::clock::sleep(Duration::from_millis(50)).await;
+        Ok::<_, BoxError>(AccountDao { pool: None })
+    }));
+    factory.register(
+        BeanDefinition::from_factory_fn(|factory_data| DaoHolder {
+            dao: factory_data.get_bean(),
+        })
+        .require::<AccountDao>(),
+    );
+    factory
+}
+
+#[actix::test]
+async fn async_provider_init_state_001() {
+    //初始化期间不能再次初始化或替换bean
+    let factory = slow_dao_factory();
+    let init_factory = factory.clone();
+    let init_handle = actix::spawn(async move { init_factory.init().await });
+    actix::clock::sleep(Duration::from_millis(10)).await;
+    assert_eq!(
+        factory.init().await.unwrap_err(),
+        BeanFactoryError::Initializing
+    );
+    assert_eq!(
+        factory
+            .replace(BeanDefinition::from_fn(|| DbPool {
+                url: "mysql://localhost".to_owned(),
+            }))
+            .await
+            .unwrap_err(),
+        BeanFactoryError::Initializing
+    );
+    assert!(init_handle.await.unwrap().is_ok());
+
+    //初始化期间关闭工厂会取消初始化
+    let factory = slow_dao_factory();
+    let init_factory = factory.clone();
+    let init_handle = actix::spawn(async move { init_factory.init().await });
+    actix::clock::sleep(Duration::from_millis(10)).await;
+    factory.shutdown().await.unwrap();
+    assert_eq!(
+        init_handle.await.unwrap().unwrap_err(),
+        BeanFactoryError::InitCancelled
+    );
+    assert!(factory.get_bean_sync::<AccountDao>().is_none());
+    assert!(factory.get_bean_sync::<DaoHolder>().is_none());
+}
+
+struct QueryReady;
+
+impl Message for QueryReady {
+    type Result = bool;
+}
+
+#[derive(Default)]
+struct SlowInjectActor {
+    ready: bool,
+}
+
+impl Actor for SlowInjectActor {
+    type Context = Context<Self>;
+}
+
+impl Inject for SlowInjectActor {
+    type Context = Context<Self>;
+
+    fn inject(&mut self, _: FactoryData, _: BeanFactory, _: &mut Self::Context) {}
+}
+
+impl Handler<FactoryEvent> for SlowInjectActor {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, msg: FactoryEvent, _ctx: &mut Self::Context) -> Self::Result {
+        let is_inject = matches!(msg, FactoryEvent::Inject { .. });
+        Box::pin(
+            actix::clock::sleep(Duration::from_millis(50))
+                .into_actor(self)
+                .map(move |_, act, _ctx| {
+                    if is_inject {
+                        act.ready = true;
+                    }
+                }),
+        )
+    }
+}
+
+impl Handler<QueryReady> for SlowInjectActor {
+    type Result = bool;
+
+    fn handle(&mut self, _msg: QueryReady, _ctx: &mut Self::Context) -> Self::Result {
+        self.ready
+    }
+}
+
+#[actix::test]
+async fn init_wait_inject_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::actor_with_inject_from_default::<
+        SlowInjectActor,
+    >());
+    let factory_data = factory.init().await.unwrap();
+    let addr: Addr<SlowInjectActor> = factory_data.get_actor().unwrap();
+    assert!(addr.send(QueryReady).await.unwrap());
+    //手写的事件处理没有停止actor,处理完销毁事件后不会一直等待
+    let start = std::time::Instant::now();
+    factory.shutdown().await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    let factory = BeanFactory::new();
+    factory.set_init_timeout(Duration::from_millis(10));
+    factory.register(BeanDefinition::actor_with_inject_from_default::<
+        SlowInjectActor,
+    >());
+    assert_eq!(
+        factory.init().await.unwrap_err(),
+        BeanFactoryError::InjectTimeout(vec![type_name::<SlowInjectActor>().to_owned()])
+    );
+}
+
+static DESTROY_LOG: Mutex<Vec<&'static str>> = Mutex::new(vec![]);
+
+struct SessionStore;
+struct SessionReport;
+
+#[bean(inject, destroy = "on_destroy")]
+#[derive(Default)]
+struct SessionActor {
+    #[inject(required)]
+    store: Option<Arc<SessionStore>>,
+}
+
+impl Actor for SessionActor {
+    type Context = Context<Self>;
+}
+
+impl SessionActor {
+    fn on_destroy(&mut self, _ctx: &mut Context<Self>) {
+        assert!(self.store.is_some());
+        DESTROY_LOG.lock().unwrap().push("session_actor");
+    }
+}
+
+#[actix::test]
+async fn shutdown_001() {
+    let factory = BeanFactory::new();
+    factory.register(
+        BeanDefinition::from_fn(|| SessionStore)
+            .on_destroy(|_: &SessionStore| DESTROY_LOG.lock().unwrap().push("session_store")),
+    );
+    factory.register(BeanDefinition::actor_with_inject_from_default::<SessionActor>());
+    factory.register(
+        BeanDefinition::from_fn(|| SessionReport)
+            .on_destroy(|_: &SessionReport| DESTROY_LOG.lock().unwrap().push("session_report"))
+            .depend_on::<SessionActor>(),
+    );
+    let factory_data = factory.init().await.unwrap();
+    let addr: Addr<SessionActor> = factory_data.get_actor().unwrap();
+    assert!(addr.connected());
+
+    factory.shutdown().await.unwrap();
+    assert_eq!(
+        *DESTROY_LOG.lock().unwrap(),
+        vec!["session_report", "session_actor", "session_store"]
+    );
+    assert!(!addr.connected());
+    assert!(factory.query_bean_names().await.len() == 3);
+    assert!(factory.get_bean::<SessionStore>().await.is_none());
+
+    let factory = BeanFactory::spawn_new();
+    factory.register(BeanDefinition::from_fn(|| SessionStore));
+    factory.init().await.unwrap();
+    factory.shutdown().await.unwrap();
+    actix::clock::sleep(Duration::from_millis(50)).await;
+    assert_eq!(
+        factory.init().await.unwrap_err(),
+        BeanFactoryError::MailboxClosed
+    );
+}
+
+#[actix::test]
+async fn sync_lookup_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::from_fn(|| DbPool {
+        url: "mysql://localhost".to_owned(),
+    }));
+    factory.register(BeanDefinition::actor_from_default::<FooActor>());
+    assert!(factory.get_bean_sync::<DbPool>().is_none());
+    factory.init().await.unwrap();
+
+    let sync_factory = factory.clone();
+    let pool = std::thread::spawn(move || sync_factory.get_bean_sync::<DbPool>())
+        .join()
+        .unwrap()
+        .unwrap();
+    assert_eq!(pool.url, "mysql://localhost");
+    let addr: Addr<FooActor> = factory.get_actor_sync().unwrap();
+    assert_eq!(addr.send(Ping(1)).await.unwrap(), 0);
+
+    let core_factory = BeanFactory::new_by_core(factory.core_addr.clone());
+    factory.query_bean_names().await;
+    assert!(core_factory.get_bean_sync::<DbPool>().is_some());
+
+    factory.shutdown().await.unwrap();
+    assert!(factory.get_bean_sync::<DbPool>().is_none());
+    assert!(core_factory.get_actor_sync::<FooActor>().is_none());
+}
+
+mod v1 {
+    pub struct Codec(pub u32);
+}
+
+mod v2 {
+    pub struct Codec(pub u32);
+}
+
+#[actix::test]
+async fn type_id_registry_001() {
+    let factory = BeanFactory::new();
+    //两个类型使用相同的类型名称,按类型获取时互不影响
+    let mut codec_v1 = BeanDefinition::from_fn(|| v1::Codec(1));
+    codec_v1.type_name = "Codec".to_owned();
+    let mut codec_v2 = BeanDefinition::from_fn(|| v2::Codec(2)).require::<v1::Codec>();
+    codec_v2.type_name = "Codec".to_owned();
+    factory.register(codec_v1);
+    factory.register(codec_v2);
+    factory.register(BeanDefinition::from_fn(|| DbPool {
+        url: "mysql://localhost".to_owned(),
+    }));
+    let factory_data = factory.init().await.unwrap();
+
+    assert_eq!(factory_data.get_bean::<v1::Codec>().unwrap().0, 1);
+    assert_eq!(factory_data.get_bean::<v2::Codec>().unwrap().0, 2);
+    assert_eq!(factory_data.bean_names_of::<v2::Codec>(), vec!["Codec"]);
+    //类型名称对应多个类型时,不能按名称获取
+    assert!(factory_data
+        .get_bean_by_name::<v1::Codec>("Codec")
+        .is_none());
+    //按类型名称获取未指定名称的bean仍然可用
+    let pool: Arc<DbPool> = factory_data
+        .get_bean_by_name(type_name::<DbPool>())
+        .unwrap();
+    assert_eq!(pool.url, "mysql://localhost");
+}
+
+trait ConfigStore: Send + Sync {
+    fn kind(&self) -> &str;
+}
+
+#[bean(register, provides = "dyn ConfigStore")]
+#[derive(Default)]
+struct MemoryConfigStore {}
+
+impl ConfigStore for MemoryConfigStore {
+    fn kind(&self) -> &str {
+        "memory"
+    }
+}
+
+struct FileConfigStore;
+
+impl ConfigStore for FileConfigStore {
+    fn kind(&self) -> &str {
+        "file"
+    }
+}
+
+struct ConfigReader {
+    store: Option<Arc<dyn ConfigStore>>,
+}
+
+#[actix::test]
+async fn trait_object_bean_001() {
+    let factory = BeanFactory::new();
+    factory.register(
+        BeanDefinition::from_factory_fn(|factory_data| ConfigReader {
+            store: factory_data.get_bean_dyn(),
+        })
+        .require::<dyn ConfigStore>(),
+    );
+    factory.register(
+        BeanDefinition::from_fn(|| FileConfigStore)
+            .provides(|v: Arc<FileConfigStore>| v as Arc<dyn ConfigStore>),
+    );
+    let factory_data = factory.init().await.unwrap();
+    let reader: Arc<ConfigReader> = factory_data.get_bean().unwrap();
+    assert_eq!(reader.store.as_ref().unwrap().kind(), "file");
+    let store = factory.get_bean_dyn::<dyn ConfigStore>().await.unwrap();
+    assert_eq!(store.kind(), "file");
+    assert!(factory_data.get_bean::<FileConfigStore>().is_some());
+
+    //`#[bean(provides = "dyn ConfigStore")]`注册的bean
+    let factory = BeanFactory::new();
+    register_beans(&factory);
+    let factory_data = factory.init().await.unwrap();
+    let store: Arc<dyn ConfigStore> = factory_data.get_bean_dyn().unwrap();
+    assert_eq!(store.kind(), "memory");
+
+    //有多个bean提供同一trait时,不能按类型获取单个bean
+    let factory = BeanFactory::new();
+    register_beans(&factory);
+    factory.register(
+        BeanDefinition::from_fn(|| FileConfigStore)
+            .provides(|v: Arc<FileConfigStore>| v as Arc<dyn ConfigStore>),
+    );
+    let factory_data = factory.init().await.unwrap();
+    assert!(factory_data.get_bean_dyn::<dyn ConfigStore>().is_none());
+}
+
+trait EventSink: Send + Sync {
+    fn name(&self) -> &'static str;
+}
+
+struct ConsoleSink;
+struct MetricsSink;
+
+impl EventSink for ConsoleSink {
+    fn name(&self) -> &'static str {
+        "console"
+    }
+}
+
+impl EventSink for MetricsSink {
+    fn name(&self) -> &'static str {
+        "metrics"
+    }
+}
+
+struct QuerySinks;
+
+impl Message for QuerySinks {
+    type Result = Vec<&'static str>;
+}
+
+#[bean(inject)]
+#[derive(Default)]
+struct SinkHub {
+    #[inject]
+    sinks: Vec<Arc<dyn EventSink>>,
+}
+
+impl Actor for SinkHub {
+    type Context = Context<Self>;
+}
+
+impl Handler<QuerySinks> for SinkHub {
+    type Result = MessageResult<QuerySinks>;
+
+    fn handle(&mut self, _msg: QuerySinks, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.sinks.iter().map(|e| e.name()).collect())
+    }
+}
+
+struct Alarm;
+
+impl Message for Alarm {
+    type Result = &'static str;
+}
+
+#[derive(Default)]
+struct AuditActor;
+
+impl Actor for AuditActor {
+    type Context = Context<Self>;
+}
+
+impl Handler<Alarm> for AuditActor {
+    type Result = MessageResult<Alarm>;
+
+    fn handle(&mut self, _msg: Alarm, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult("audit")
+    }
+}
+
+#[derive(Default)]
+struct PagerActor;
+
+impl Actor for PagerActor {
+    type Context = Context<Self>;
+}
+
+impl Handler<Alarm> for PagerActor {
+    type Result = MessageResult<Alarm>;
+
+    fn handle(&mut self, _msg: Alarm, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult("pager")
+    }
+}
+
+#[actix::test]
+async fn collection_inject_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::actor_with_inject_from_default::<SinkHub>());
+    factory.register(
+        BeanDefinition::from_fn(|| MetricsSink)
+            .provides(|v: Arc<MetricsSink>| v as Arc<dyn EventSink>),
+    );
+    factory.register(
+        BeanDefinition::from_fn(|| ConsoleSink)
+            .provides(|v: Arc<ConsoleSink>| v as Arc<dyn EventSink>),
+    );
+    factory.register(
+        BeanDefinition::actor_from_default::<PagerActor>().handles::<PagerActor, Alarm>(),
+    );
+    factory.register(
+        BeanDefinition::actor_from_default::<AuditActor>().handles::<AuditActor, Alarm>(),
+    );
+    let factory_data = factory.init().await.unwrap();
+
+    let names: Vec<&str> = factory_data
+        .get_beans_of::<dyn EventSink>()
+        .iter()
+        .map(|e| e.name())
+        .collect();
+    assert_eq!(names, vec!["console", "metrics"]);
+    let hub: Addr<SinkHub> = factory_data.get_actor().unwrap();
+    assert_eq!(hub.send(QuerySinks).await.unwrap(), names);
+
+    let mut replies = vec![];
+    for recipient in factory.get_recipients::<Alarm>().await {
+        replies.push(recipient.send(Alarm).await.unwrap());
+    }
+    assert_eq!(replies, vec!["audit", "pager"]);
+}
+
+#[test]
+#[should_panic(expected = "can not handle messages")]
+fn handles_type_mismatch_001() {
+    let _ = BeanDefinition::actor_from_default::<AuditActor>().handles::<PagerActor, Alarm>();
+}
+
+struct RouteAlarm;
+
+impl Message for RouteAlarm {
+    type Result = (Option<&'static str>, usize);
+}
+
+#[bean(inject)]
+#[derive(Default)]
+struct AlarmRouter {
+    #[inject(name = "pager", required)]
+    pager: Option<Recipient<Alarm>>,
+    #[inject]
+    all: Vec<Recipient<Alarm>>,
+}
+
+impl Actor for AlarmRouter {
+    type Context = Context<Self>;
+}
+
+impl Handler<RouteAlarm> for AlarmRouter {
+    type Result = ResponseActFuture<Self, (Option<&'static str>, usize)>;
+
+    fn handle(&mut self, _msg: RouteAlarm, _ctx: &mut Self::Context) -> Self::Result {
+        let pager = self.pager.clone();
+        let count = self.all.len();
+        Box::pin(
+            async move {
+                match pager {
+                    Some(pager) => (pager.send(Alarm).await.ok(), count),
+                    None => (None, count),
+                }
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+#[actix::test]
+async fn recipient_inject_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::actor_with_inject_from_default::<AlarmRouter>());
+    factory.register(
+        BeanDefinition::actor_from_default::<PagerActor>()
+            .with_name("pager")
+            .handles::<PagerActor, Alarm>(),
+    );
+    factory.register(
+        BeanDefinition::actor_from_default::<AuditActor>()
+            .with_name("audit")
+            .handles::<AuditActor, Alarm>(),
+    );
+    let factory_data = factory.init().await.unwrap();
+    //有多个actor处理该消息时,需要按名称获取
+    assert!(factory_data.get_recipient::<Alarm>().is_none());
+    let audit = factory
+        .get_recipient_by_name::<Alarm>("audit")
+        .await
+        .unwrap();
+    assert_eq!(audit.send(Alarm).await.unwrap(), "audit");
+
+    let router: Addr<AlarmRouter> = factory_data.get_actor().unwrap();
+    assert_eq!(router.send(RouteAlarm).await.unwrap(), (Some("pager"), 2));
+}
+
+#[bean(register, primary, provides = "dyn EventSink")]
+#[derive(Default)]
+struct AuditLogSink {}
+
+impl EventSink for AuditLogSink {
+    fn name(&self) -> &'static str {
+        "audit_log"
+    }
+}
+
+#[actix::test]
+async fn primary_bean_001() {
+    let pool = |url: &'static str| pool_bean(url).with_name(url);
+    let factory = BeanFactory::new();
+    factory.register(pool("primary"));
+    factory.register(pool("replica"));
+    let factory_data = factory.init().await.unwrap();
+    assert!(factory_data.get_bean::<ConnectionPool>().is_none());
+    match factory.try_get_bean::<ConnectionPool>().await {
+        Err(BeanFactoryError::AmbiguousBean {
+            type_name: name,
+            mut candidates,
+        }) => {
+            assert_eq!(name, type_name::<ConnectionPool>());
+            candidates.sort();
+            assert_eq!(candidates, vec!["primary", "replica"]);
+        }
+        _ => panic!("expected ambiguous bean error"),
+    }
+
+    //注入字段无法选出唯一的bean时初始化失败
+    let factory = BeanFactory::new();
+    factory.register(pool("primary"));
+    factory.register(pool("replica"));
+    factory.register(BeanDefinition::actor_with_inject_from_default::<PoolWatcher>());
+    assert!(matches!(
+        factory.init().await,
+        Err(BeanFactoryError::AmbiguousBean { .. })
+    ));
+
+    let factory = BeanFactory::new();
+    factory.register(pool("primary").primary());
+    factory.register(pool("replica"));
+    let factory_data = factory.init().await.unwrap();
+    assert_eq!(
+        factory_data.get_bean::<ConnectionPool>().unwrap().url,
+        "primary"
+    );
+    assert_eq!(factory_data.get_beans::<ConnectionPool>().len(), 2);
+
+    //未指定名称的bean不会被优先选择,按类型名称仍可以获取
+    let factory = BeanFactory::new();
+    factory.register(pool_bean("default"));
+    factory.register(pool("replica"));
+    let factory_data = factory.init().await.unwrap();
+    assert!(factory_data.get_bean::<ConnectionPool>().is_none());
+    assert!(matches!(
+        factory.try_get_bean::<ConnectionPool>().await,
+        Err(BeanFactoryError::AmbiguousBean { .. })
+    ));
+    assert_eq!(
+        factory_data
+            .get_bean_by_name::<ConnectionPool>(type_name::<ConnectionPool>())
+            .unwrap()
+            .url,
+        "default"
+    );
+
+    //`#[bean(primary)]`
+    let factory = BeanFactory::new();
+    register_beans(&factory);
+    factory.register(
+        BeanDefinition::from_fn(|| ConsoleSink)
+            .provides(|v: Arc<ConsoleSink>| v as Arc<dyn EventSink>),
+    );
+    factory.init().await.unwrap();
+    let sink = factory.try_get_bean_dyn::<dyn EventSink>().await.unwrap();
+    assert_eq!(sink.name(), "audit_log");
+}
+
+struct PoolUser {
+    url: String,
+}
+
+#[actix::test]
+async fn hierarchical_factory_001() {
+    let parent = BeanFactory::new();
+    parent.register(BeanDefinition::from_fn(|| ConnectionPool {
+        url: "parent".to_owned(),
+    }));
+    parent.init().await.unwrap();
+
+    let child = parent.new_child();
+    child.register(
+        BeanDefinition::from_factory_fn(|factory_data| PoolUser {
+            url: factory_data
+                .get_bean::<ConnectionPool>()
+                .unwrap()
+                .url
+                .clone(),
+        })
+        .require::<ConnectionPool>(),
+    );
+    let child_data = child.init().await.unwrap();
+    assert_eq!(child_data.get_bean::<PoolUser>().unwrap().url, "parent");
+    assert_eq!(
+        child.get_bean::<ConnectionPool>().await.unwrap().url,
+        "parent"
+    );
+    assert!(child_data
+        .get_bean_by_name::<ConnectionPool>(type_name::<ConnectionPool>())
+        .is_some());
+    assert!(parent.get_bean::<PoolUser>().await.is_none());
+
+    child.shutdown().await.unwrap();
+    assert!(child.get_bean::<PoolUser>().await.is_none());
+    assert_eq!(
+        parent.get_bean::<ConnectionPool>().await.unwrap().url,
+        "parent"
+    );
+}
+
+#[bean(register, profile = "conditional_test")]
+#[derive(Default)]
+struct ProfiledComponent;
+
+struct AuditFeature;
+
+#[actix::test]
+async fn conditional_bean_001() {
+    let factory = BeanFactory::new();
+    factory.set_active_profiles(&["dev"]);
+    factory.register(pool_bean("dev").profile("dev"));
+    factory.register(pool_bean("prod").profile("prod"));
+    factory.register(pool_bean("default").on_missing_bean());
+    factory.register(BeanDefinition::from_fn(|| AuditFeature).on_property("feature.audit"));
+    let factory_data = factory.init().await.unwrap();
+    assert_eq!(
+        factory_data.get_bean::<ConnectionPool>().unwrap().url,
+        "dev"
+    );
+    assert_eq!(factory_data.get_beans::<ConnectionPool>().len(), 1);
+    assert!(factory_data.get_bean::<AuditFeature>().is_none());
+    assert!(factory_data.get_bean::<ProfiledComponent>().is_none());
+
+    let factory = BeanFactory::new();
+    factory.set_property("feature.audit", "true");
+    factory.register(pool_bean("prod").profile("prod"));
+    factory.register(pool_bean("default").on_missing_bean());
+    factory.register(BeanDefinition::from_fn(|| AuditFeature).on_property("feature.audit"));
+    let factory_data = factory.init().await.unwrap();
+    assert_eq!(
+        factory_data.get_bean::<ConnectionPool>().unwrap().url,
+        "default"
+    );
+    assert!(factory_data.get_bean::<AuditFeature>().is_some());
+
+    //提供的trait object类型已有bean时,不注册默认实现
+    let memory_store = || {
+        BeanDefinition::from_fn(MemoryConfigStore::default)
+            .provides(|v: Arc<MemoryConfigStore>| v as Arc<dyn ConfigStore>)
+            .on_missing_bean()
+    };
+    let factory = BeanFactory::new();
+    factory.register(
+        BeanDefinition::from_fn(|| FileConfigStore)
+            .provides(|v: Arc<FileConfigStore>| v as Arc<dyn ConfigStore>),
+    );
+    factory.register(memory_store());
+    factory.init().await.unwrap();
+    let store = factory.try_get_bean_dyn::<dyn ConfigStore>().await.unwrap();
+    assert_eq!(store.kind(), "file");
+    assert!(factory.get_bean::<MemoryConfigStore>().await.is_none());
+
+    let factory = BeanFactory::new();
+    factory.register(memory_store());
+    factory.init().await.unwrap();
+    let store = factory.try_get_bean_dyn::<dyn ConfigStore>().await.unwrap();
+    assert_eq!(store.kind(), "memory");
+
+    //`#[bean(profile = "...")]`
+    let factory = BeanFactory::new();
+    factory.set_active_profiles(&["conditional_test"]);
+    register_beans(&factory);
+    let factory_data = factory.init().await.unwrap();
+    assert!(factory_data.get_bean::<ProfiledComponent>().is_some());
+}
+
+#[bean(inject)]
+#[derive(Default)]
+struct DbSettings {
+    #[inject(property = "db.url")]
+    url: Option<String>,
+    #[inject(property = "db.port")]
+    port: Option<u16>,
+}
+
+impl Actor for DbSettings {
+    type Context = Context<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "(Option<String>, Option<u16>)")]
+struct QueryDbSettings;
+
+impl Handler<QueryDbSettings> for DbSettings {
+    type Result = MessageResult<QueryDbSettings>;
+
+    fn handle(&mut self, _msg: QueryDbSettings, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult((self.url.clone(), self.port))
+    }
+}
+
+#[actix::test]
+async fn property_001() {
+    let dir = std::env::temp_dir().join(format!("bean_factory_property_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let toml_file = dir.join("app.toml");
+    std::fs::write(
+        &toml_file,
+        "[db]\nurl = \"toml\"\nport = 5432\ntimeout = \"soon\"\n",
+    )
+    .unwrap();
+    let json_file = dir.join("app.json");
+    std::fs::write(&json_file, r#"{"db": {"url": "json"}}"#).unwrap();
+    std::env::set_var("BF_PROPERTY_TEST_DB__PORT", "6543");
+    std::env::set_var("BF_PROPERTY_TEST_DB__MAX_SIZE", "8");
+
+    let factory = BeanFactory::new();
+    factory.add_property_file(&toml_file);
+    factory.add_property_file(&json_file);
+    factory.add_env_properties("BF_PROPERTY_TEST");
+    factory.set_property("db.user", "admin");
+    factory.register(BeanDefinition::actor_with_inject_from_default::<DbSettings>());
+    let factory_data = factory.init().await.unwrap();
+    assert_eq!(
+        factory_data.get_property::<String>("db.url").unwrap(),
+        "json"
+    );
+    assert_eq!(factory_data.get_property::<u16>("db.port").unwrap(), 6543);
+    assert_eq!(factory_data.get_property::<u32>("db.max_size").unwrap(), 8);
+    assert_eq!(
+        factory_data.get_property::<String>("db.user").unwrap(),
+        "admin"
+    );
+    assert_eq!(
+        factory_data.get_property::<String>("db.password"),
+        Err(BeanFactoryError::PropertyNotFound("db.password".to_owned()))
+    );
+    match factory_data.get_property::<u64>("db.timeout") {
+        Err(BeanFactoryError::InvalidProperty { key, .. }) => assert_eq!(key, "db.timeout"),
+        _ => panic!("expected invalid property error"),
+    }
+    let settings: Addr<DbSettings> = factory_data.get_actor().unwrap();
+    let (url, port) = settings.send(QueryDbSettings).await.unwrap();
+    assert_eq!(url.as_deref(), Some("json"));
+    assert_eq!(port, Some(6543));
+
+    let factory = BeanFactory::new();
+    factory.add_property_file(dir.join("missing.toml"));
+    match factory.init().await {
+        Err(BeanFactoryError::PropertySource(_)) => {}
+        _ => panic!("expected property source error"),
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ServerConfig {
+    host: String,
+    port: u16,
+}
+
+#[bean(config = "cache", validate = "check", profile = "config_test")]
+#[derive(serde::Deserialize)]
+struct CacheConfig {
+    size: usize,
+}
+
+impl CacheConfig {
+    fn check(&self) -> Result<(), String> {
+        if self.size == 0 {
+            return Err("cache.size must be greater than 0".to_owned());
+        }
+        Ok(())
+    }
+}
+
+#[actix::test]
+async fn config_bean_001() {
+    let validated = || {
+        BeanDefinition::from_config_with("server", |config: &ServerConfig| {
+            if config.port == 0 {
+                return Err("server.port must not be 0");
+            }
+            Ok(())
+        })
+    };
+    let factory = BeanFactory::new();
+    factory.set_property("server.host", "localhost");
+    factory.set_property("server.port", "8080");
+    factory.register(validated());
+    let factory_data = factory.init().await.unwrap();
+    let config = factory_data.get_bean::<ServerConfig>().unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+
+    let factory = BeanFactory::new();
+    factory.set_property("server.host", "localhost");
+    factory.set_property("server.port", "0");
+    factory.register(validated());
+    match factory.init().await {
+        Err(BeanFactoryError::ProviderFailed { message, .. }) => {
+            assert_eq!(message, "server.port must not be 0")
+        }
+        _ => panic!("expected validation error"),
+    }
+
+    let factory = BeanFactory::new();
+    factory.set_property("server.port", "http");
+    factory.register(BeanDefinition::from_config::<ServerConfig>("server"));
+    match factory.init().await {
+        Err(BeanFactoryError::ProviderFailed { bean, message }) => {
+            assert_eq!(bean, type_name::<ServerConfig>());
+            assert!(message.starts_with("invalid property server"));
+        }
+        _ => panic!("expected invalid property error"),
+    }
+
+    //`#[bean(config = "...", validate = "...")]`
+    let factory = BeanFactory::new();
+    factory.set_active_profiles(&["config_test"]);
+    factory.set_property("cache.size", "0");
+    register_beans(&factory);
+    let errors = factory.init().await.unwrap_err();
+    assert!(errors
+        .errors()
+        .contains(&&BeanFactoryError::ProviderFailed {
+            bean: type_name::<CacheConfig>().to_owned(),
+            message: "cache.size must be greater than 0".to_owned(),
+        }));
+}
+
+#[bean(inject, refreshed = "on_refreshed")]
+#[derive(Default)]
+struct PoolWatcher {
+    #[inject]
+    pool: Option<Arc<ConnectionPool>>,
+    refreshed: Vec<String>,
+}
+
+impl PoolWatcher {
+    fn on_refreshed(&mut self, bean: String, _ctx: &mut Context<Self>) {
+        self.refreshed.push(bean);
+    }
+}
+
+impl Actor for PoolWatcher {
+    type Context = Context<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "(Option<String>, Vec<String>)")]
+struct QueryPoolWatcher;
+
+impl Handler<QueryPoolWatcher> for PoolWatcher {
+    type Result = MessageResult<QueryPoolWatcher>;
+
+    fn handle(&mut self, _msg: QueryPoolWatcher, _ctx: &mut Self::Context) -> Self::Result {
+        let url = self.pool.as_ref().map(|e| e.url.clone());
+        MessageResult((url, self.refreshed.clone()))
+    }
+}
+
+#[actix::test]
+async fn replace_bean_001() {
+    let destroyed = Arc::new(Mutex::new(vec![]));
+    let pool = |url: &'static str| {
+        let destroyed = destroyed.clone();
+        pool_bean(url)
+            .on_destroy(move |v: &ConnectionPool| destroyed.lock().unwrap().push(v.url.clone()))
+    };
+    let factory = BeanFactory::new();
+    factory.register(pool("v1"));
+    factory.register(BeanDefinition::actor_with_inject_from_default::<PoolWatcher>());
+    let factory_data = factory.init().await.unwrap();
+    let watcher: Addr<PoolWatcher> = factory_data.get_actor().unwrap();
+    let (url, refreshed) = watcher.send(QueryPoolWatcher).await.unwrap();
+    assert_eq!(url.as_deref(), Some("v1"));
+    assert!(refreshed.is_empty());
+
+    let factory_data = factory.replace(pool("v2")).await.unwrap();
+    assert_eq!(factory_data.get_bean::<ConnectionPool>().unwrap().url, "v2");
+    assert_eq!(factory.get_bean_sync::<ConnectionPool>().unwrap().url, "v2");
+    let (url, refreshed) = watcher.send(QueryPoolWatcher).await.unwrap();
+    assert_eq!(url.as_deref(), Some("v2"));
+    assert_eq!(refreshed, vec![type_name::<ConnectionPool>()]);
+    assert_eq!(*destroyed.lock().unwrap(), vec!["v1"]);
+
+    let failed =
+        BeanDefinition::from_try_fn(|_| -> Result<ConnectionPool, &str> { Err("connect refused") });
+    assert!(factory.replace(failed).await.is_err());
+    assert_eq!(factory.get_bean_sync::<ConnectionPool>().unwrap().url, "v2");
+    assert_eq!(*destroyed.lock().unwrap(), vec!["v1"]);
+
+    //初始化后新增的bean直接创建
+    let factory_data = factory
+        .replace(BeanDefinition::from_fn(|| HeavyCache))
+        .await
+        .unwrap();
+    assert!(factory_data.get_bean::<HeavyCache>().is_some());
+    assert!(factory.get_bean_sync::<HeavyCache>().is_some());
+    let missing = BeanDefinition::from_fn(|| ReportService).require::<MailClient>();
+    assert_eq!(
+        factory.replace(missing).await.unwrap_err(),
+        BeanFactoryError::MissingDependency {
+            bean: type_name::<ReportService>().to_owned(),
+            dependency: type_name::<MailClient>().to_owned(),
+        }
+    );
+    assert!(factory.get_bean_sync::<ReportService>().is_none());
+}
+
+#[bean(inject, supervised, complete = "on_complete")]
+#[derive(Default)]
+struct SupervisedWorker {
+    #[inject]
+    pool: Option<Arc<ConnectionPool>>,
+    injected: usize,
+}
+
+impl SupervisedWorker {
+    fn on_complete(&mut self, _ctx: &mut Context<Self>) {
+        self.injected += 1;
+    }
+}
+
+impl Actor for SupervisedWorker {
+    type Context = Context<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Crash;
+
+impl Handler<Crash> for SupervisedWorker {
+    type Result = ();
+
+    fn handle(&mut self, _msg: Crash, ctx: &mut Self::Context) -> Self::Result {
+        ctx.stop();
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "usize")]
+struct QueryInjectCount;
+
+impl Handler<QueryInjectCount> for SupervisedWorker {
+    type Result = usize;
+
+    fn handle(&mut self, _msg: QueryInjectCount, _ctx: &mut Self::Context) -> Self::Result {
+        self.injected
+    }
+}
+
+#[bean(inject, refreshed = "on_refreshed")]
+#[derive(Default)]
+struct WorkerClient {
+    #[inject]
+    worker: Option<Addr<SupervisedWorker>>,
+    refreshed: Vec<String>,
+}
+
+impl WorkerClient {
+    fn on_refreshed(&mut self, bean: String, _ctx: &mut Context<Self>) {
+        self.refreshed.push(bean);
+    }
+}
+
+impl Actor for WorkerClient {
+    type Context = Context<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<String>")]
+struct QueryRefreshed;
+
+impl Handler<QueryRefreshed> for WorkerClient {
+    type Result = MessageResult<QueryRefreshed>;
+
+    fn handle(&mut self, _msg: QueryRefreshed, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.refreshed.clone())
+    }
+}
+
+#[actix::test]
+async fn supervised_actor_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::from_fn(|| ConnectionPool {
+        url: "supervised".to_owned(),
+    }));
+    factory.register(BeanDefinition::supervised_actor_from_default::<
+        SupervisedWorker,
+    >());
+    factory.register(BeanDefinition::actor_with_inject_from_default::<WorkerClient>());
+    let factory_data = factory.init().await.unwrap();
+    let worker: Addr<SupervisedWorker> = factory_data.get_actor().unwrap();
+    let client: Addr<WorkerClient> = factory_data.get_actor().unwrap();
+    assert_eq!(worker.send(QueryInjectCount).await.unwrap(), 1);
+
+    worker.do_send(Crash);
+    let mut refreshed = vec![];
+    for _ in 0..100 {
+        refreshed = client.send(QueryRefreshed).await.unwrap();
+        if !refreshed.is_empty() {
+            break;
+        }
+        actix::clock::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(refreshed, vec![type_name::<SupervisedWorker>()]);
+    assert!(worker.connected());
+    assert_eq!(worker.send(QueryInjectCount).await.unwrap(), 2);
+
+    //还有地址时受监督的actor会被重启,关闭前释放工厂外持有的地址
+    let weak = worker.downgrade();
+    drop((worker, client, factory_data));
+    factory.shutdown().await.unwrap();
+    assert!(weak.upgrade().is_none());
+}
+
+static SYNC_INJECTED: Mutex<Vec<(std::thread::ThreadId, bool)>> = Mutex::new(vec![]);
+
+#[bean(inject, sync, complete = "on_complete")]
+#[derive(Default)]
+struct HashWorker {
+    #[inject]
+    pool: Option<Arc<ConnectionPool>>,
+}
+
+impl HashWorker {
+    fn on_complete(&mut self, _ctx: &mut SyncContext<Self>) {
+        SYNC_INJECTED
+            .lock()
+            .unwrap()
+            .push((std::thread::current().id(), self.pool.is_some()));
+    }
+}
+
+impl Actor for HashWorker {
+    type Context = SyncContext<Self>;
+}
+
+#[actix::test]
+async fn sync_actor_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::from_fn(|| ConnectionPool {
+        url: "sync".to_owned(),
+    }));
+    factory.register(BeanDefinition::sync_actor_from_default::<HashWorker>(3));
+    let factory_data = factory.init().await.unwrap();
+    assert!(factory_data.get_actor::<HashWorker>().is_some());
+    let injected = SYNC_INJECTED.lock().unwrap().clone();
+    assert_eq!(injected.len(), 3);
+    assert!(injected.iter().all(|(_, pool)| *pool));
+    let threads: HashSet<_> = injected.iter().map(|(thread_id, _)| *thread_id).collect();
+    assert_eq!(threads.len(), 3);
+
+    //所有地址都释放后SyncArbiter的线程才会退出
+    let weak = factory_data.get_actor::<HashWorker>().unwrap().downgrade();
+    drop(factory_data);
+    factory.shutdown().await.unwrap();
+    assert!(weak.upgrade().is_none());
+}
+
+#[bean(inject, sync)]
+#[derive(Default)]
+struct PanicWorker {
+    #[inject]
+    pool: Option<Arc<ConnectionPool>>,
+}
+
+impl Actor for PanicWorker {
+    type Context = SyncContext<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "String")]
+struct Work(bool);
+
+impl Handler<Work> for PanicWorker {
+    type Result = String;
+
+    fn handle(&mut self, msg: Work, _ctx: &mut Self::Context) -> Self::Result {
+        if msg.0 {
+            panic!("worker panic");
+        }
+        self.pool
+            .as_ref()
+            .map(|v| v.url.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[actix::test]
+async fn sync_actor_panic_001() {
+    let factory = BeanFactory::new();
+    factory.register(BeanDefinition::from_fn(|| ConnectionPool {
+        url: "old".to_owned(),
+    }));
+    factory.register(BeanDefinition::sync_actor_from_default::<PanicWorker>(2));
+    let factory_data = factory.init().await.unwrap();
+    let worker: Addr<PanicWorker> = factory_data.get_actor().unwrap();
+    assert!(worker.send(Work(true)).await.is_err());
+
+    //处理事件的线程已退出,替换依赖时只等待运行中的线程
+    factory
+        .replace(BeanDefinition::from_fn(|| ConnectionPool {
+            url: "new".to_owned(),
+        }))
+        .await
+        .unwrap();
+    for _ in 0..4 {
+        let url = actix::clock::timeout(Duration::from_secs(1), worker.send(Work(false)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(url, "new");
+    }
+
+    //还持有地址时线程不会退出
+    let e = factory.shutdown().await.unwrap_err();
+    assert_eq!(
+        e,
+        BeanFactoryError::DestroyTimeout(vec![type_name::<PanicWorker>().to_owned()])
+    );
+}
+
 async fn take(factory: &BeanFactory) {
     let component: Addr<MyActor> = factory.get_actor().await.unwrap();
     let c = component.send(Ping(2)).await.unwrap();